@@ -0,0 +1,640 @@
+//! Derive macros for the [`assign`](https://docs.rs/assign) crate.
+//!
+//! This crate is not meant to be used directly. Enable the `derive` feature of
+//! `assign` instead, which re-exports everything defined here.
+
+extern crate proc_macro;
+
+use proc_macro::TokenStream;
+use proc_macro2::{Delimiter, Spacing, Span, TokenStream as TokenStream2, TokenTree};
+use quote::{format_ident, quote, quote_spanned, ToTokens};
+use syn::{
+    parse_macro_input, parse_quote, Data, DeriveInput, Field, Fields, Ident, Token, Visibility,
+};
+
+/// Implement `assign::Assign` for a struct, and optionally generate its patch
+/// type.
+///
+/// The generated implementation lists every field of the struct with its name,
+/// type and visibility, in declaration order. Fields of tuple structs are named
+/// by their position.
+///
+/// With `#[assign(patch)]`, a struct `TPatch` with the same visibility is
+/// generated next to a struct `T`, or a struct of another name with
+/// `#[assign(patch = Name)]`. It has the same fields, each wrapped in an
+/// `Option`, and implements `assign::Patch` to apply the fields that are `Some`
+/// to a `T`. It implements `Debug` and `Clone` if the field types do.
+///
+/// ```
+/// use assign::{Assign, Patch, Visibility};
+///
+/// #[derive(Assign)]
+/// #[assign(patch)]
+/// pub struct Server {
+///     pub host: String,
+///     pub(crate) port: u16,
+///     timeout: Option<u64>,
+/// }
+///
+/// let names: Vec<_> = Server::FIELDS.iter().map(|field| field.name).collect();
+/// assert_eq!(names, ["host", "port", "timeout"]);
+///
+/// let port = Server::field("port").unwrap();
+/// assert_eq!(port.ty, "u16");
+/// assert_eq!(port.visibility, Visibility::Restricted("crate"));
+///
+/// let mut server = Server { host: "localhost".into(), port: 80, timeout: None };
+/// let patch = ServerPatch { port: Some(8080), ..Default::default() };
+/// patch.apply(&mut server);
+/// assert_eq!(server.port, 8080);
+/// ```
+#[proc_macro_derive(Assign, attributes(assign))]
+pub fn derive_assign(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_assign(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Implement `assign::ApplyPartial` for a struct with named fields.
+///
+/// Fields present in a partial document are deserialized and replace the
+/// current value. Fields marked with `#[assign(nested)]` are updated with their
+/// own `ApplyPartial` implementation instead, so that a nested document only
+/// replaces the fields it contains.
+///
+/// This requires the `derive` and `serde` features of `assign`.
+///
+/// ```
+/// use assign::ApplyPartial;
+///
+/// #[derive(ApplyPartial)]
+/// struct Limits {
+///     memory: u64,
+///     #[assign(nested)]
+///     cpu: Cpu,
+/// }
+///
+/// #[derive(ApplyPartial)]
+/// struct Cpu {
+///     cores: u8,
+///     shares: u32,
+/// }
+///
+/// let mut limits = Limits { memory: 512, cpu: Cpu { cores: 1, shares: 1024 } };
+/// let mut document = serde_json::Deserializer::from_str(r#"{ "cpu": { "cores": 4 } }"#);
+/// limits.apply_partial(&mut document).unwrap();
+/// assert_eq!((limits.cpu.cores, limits.cpu.shares), (4, 1024));
+/// ```
+#[proc_macro_derive(ApplyPartial, attributes(assign))]
+pub fn derive_apply_partial(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_apply_partial(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+fn expand_apply_partial(mut input: DeriveInput) -> syn::Result<TokenStream2> {
+    let fields = match struct_fields(&input)? {
+        Fields::Named(fields) => fields.named.clone(),
+        fields => {
+            return Err(syn::Error::new_spanned(
+                fields,
+                "`#[derive(ApplyPartial)]` only supports structs with named fields",
+            ))
+        }
+    };
+
+    let mut names = Vec::new();
+    let mut arms = Vec::new();
+    let where_clause = input.generics.make_where_clause();
+    for field in &fields {
+        let ident = field.ident.as_ref().unwrap();
+        let name = ident.to_string();
+        let name = name.trim_start_matches("r#").to_owned();
+        let ty = &field.ty;
+        if is_nested(field)? {
+            where_clause
+                .predicates
+                .push(parse_quote!(#ty: ::assign::ApplyPartial));
+            arms.push(quote! {
+                #name => map.next_value_seed(::assign::__private::PartialSeed::new(&mut self.#ident, path)),
+            });
+        } else {
+            where_clause
+                .predicates
+                .push(parse_quote!(#ty: ::assign::__private::serde::de::DeserializeOwned));
+            arms.push(quote! {
+                #name => map.next_value().map(|value| self.#ident = value),
+            });
+        }
+        names.push(name);
+    }
+
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::assign::ApplyPartial for #ident #ty_generics #where_clause {
+            const PARTIAL_FIELDS: &'static [&'static str] = &[#(#names),*];
+
+            #[allow(unused_variables)]
+            fn apply_partial_field<'de, A: ::assign::__private::serde::de::MapAccess<'de>>(
+                &mut self,
+                field: &'static str,
+                map: &mut A,
+                path: &mut ::assign::__private::Vec<&'static str>,
+            ) -> ::core::result::Result<(), A::Error> {
+                match field {
+                    #(#arms)*
+                    _ => ::core::result::Result::Ok(()),
+                }
+            }
+        }
+    })
+}
+
+fn is_nested(field: &Field) -> syn::Result<bool> {
+    let mut nested = false;
+    for attr in &field.attrs {
+        if attr.path().is_ident("assign") {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("nested") {
+                    nested = true;
+                    Ok(())
+                } else {
+                    Err(meta.error("expected `nested`"))
+                }
+            })?;
+        }
+    }
+    Ok(nested)
+}
+
+/// Implement `assign::DynAssign` for a struct.
+///
+/// Every field can be assigned by its name, or by its position for tuple
+/// structs. This requires the `derive` and `alloc` features of `assign`, and
+/// all field types to be `'static`.
+///
+/// ```
+/// use assign::DynAssign;
+///
+/// #[derive(DynAssign)]
+/// struct Color(u8, u8, u8);
+///
+/// let mut color = Color(0, 0, 0);
+/// color.set_field("1", Box::new(255u8)).unwrap();
+/// assert_eq!(color.1, 255);
+/// assert!(color.set_field("3", Box::new(255u8)).is_err());
+/// ```
+#[proc_macro_derive(DynAssign)]
+pub fn derive_dyn_assign(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_dyn_assign(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+fn expand_dyn_assign(mut input: DeriveInput) -> syn::Result<TokenStream2> {
+    let fields = struct_fields(&input)?.clone();
+
+    let mut names = Vec::new();
+    let mut arms = Vec::new();
+    let where_clause = input.generics.make_where_clause();
+    for (member, field) in fields.members().zip(&fields) {
+        let name = match &member {
+            syn::Member::Named(ident) => ident.to_string().trim_start_matches("r#").to_owned(),
+            syn::Member::Unnamed(index) => index.index.to_string(),
+        };
+        let ty = &field.ty;
+        let ty_name = tokens_to_string(ty.to_token_stream());
+        where_clause.predicates.push(parse_quote!(#ty: 'static));
+        arms.push(quote! {
+            #name => ::assign::__private::downcast::<#ty>(value, #name, #ty_name)
+                .map(|value| self.#member = value),
+        });
+        names.push(name);
+    }
+
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::assign::DynAssign for #ident #ty_generics #where_clause {
+            fn field_names(&self) -> &'static [&'static str] {
+                &[#(#names),*]
+            }
+
+            fn set_field(
+                &mut self,
+                name: &str,
+                value: ::assign::__private::Box<dyn ::core::any::Any>,
+            ) -> ::core::result::Result<(), ::assign::FieldError> {
+                match name {
+                    #(#arms)*
+                    _ => ::core::result::Result::Err(::assign::FieldError::UnknownField(name.into())),
+                }
+            }
+        }
+    })
+}
+
+/// Report a compile error at an entry, used by `assign!`.
+///
+/// The input is `[tokens] message`, which expands to `compile_error!(message)`
+/// spanned at the first identifier or literal of `tokens`, or at their first
+/// token if there is none. With `[tokens] note [other] (note) message`, a
+/// second error with the message `note` is reported at `other`.
+#[doc(hidden)]
+#[proc_macro]
+pub fn error(input: TokenStream) -> TokenStream {
+    let mut tokens = TokenStream2::from(input).into_iter().peekable();
+    let span = entry_span(tokens.next());
+    let note = match tokens.peek() {
+        Some(TokenTree::Ident(ident)) if ident == "note" => {
+            tokens.next();
+            let span = entry_span(tokens.next());
+            let note = match tokens.next() {
+                Some(TokenTree::Group(note)) => note.stream(),
+                _ => TokenStream2::new(),
+            };
+            Some(quote_spanned!(span=> ::core::compile_error!(::core::concat!(#note))))
+        }
+        _ => None,
+    };
+    let message: TokenStream2 = tokens.collect();
+    let error = quote_spanned!(span=> ::core::compile_error!(::core::concat!(#message)));
+    match note {
+        Some(note) => quote!(#error; #note).into(),
+        None => error.into(),
+    }
+}
+
+/// The span of the first identifier or literal of a group of tokens, or of
+/// their first token if there is none.
+fn entry_span(group: Option<TokenTree>) -> Span {
+    match group {
+        Some(TokenTree::Group(group)) => {
+            let mut tokens = Vec::new();
+            flatten_tokens(group.stream(), &mut tokens);
+            tokens
+                .iter()
+                .find(|token| matches!(token, TokenTree::Ident(_) | TokenTree::Literal(_)))
+                .or_else(|| tokens.first())
+                .map_or_else(Span::call_site, TokenTree::span)
+        }
+        _ => Span::call_site(),
+    }
+}
+
+/// Check that a field is not assigned twice, used by `assign!`.
+///
+/// The input is `[[path]...] [path]`, the paths of the fields already assigned
+/// by a block and the path of the next field. If it was assigned before, or a
+/// field containing it or contained in it was, this expands to the `assign!`
+/// rule reporting the conflict. Paths with index entries are not checked.
+#[doc(hidden)]
+#[proc_macro]
+pub fn unique(input: TokenStream) -> TokenStream {
+    let mut tokens = TokenStream2::from(input).into_iter();
+    let (seen, path) = match (tokens.next(), tokens.next()) {
+        (Some(TokenTree::Group(seen)), Some(TokenTree::Group(path))) => (seen, path),
+        _ => return TokenStream::new(),
+    };
+    let key = path_key(&path);
+    if key.is_empty() {
+        return TokenStream::new();
+    }
+    seen.stream()
+        .into_iter()
+        .find_map(|first| match first {
+            TokenTree::Group(first) => {
+                let first_key = path_key(&first);
+                if first_key == key {
+                    Some(quote!(::assign::assign!(@duplicate #first #path);))
+                } else if !first_key.is_empty()
+                    && (first_key.starts_with(&key) || key.starts_with(&first_key))
+                {
+                    Some(quote!(::assign::assign!(@overlap #first #path);))
+                } else {
+                    None
+                }
+            }
+            _ => None,
+        })
+        .unwrap_or_default()
+        .into()
+}
+
+/// The tokens of a field path as strings, or nothing for a path with an index
+/// entry.
+fn path_key(path: &proc_macro2::Group) -> Vec<String> {
+    let mut tokens = Vec::new();
+    flatten_tokens(path.stream(), &mut tokens);
+    if tokens
+        .iter()
+        .any(|token| matches!(token, TokenTree::Group(_)))
+    {
+        return Vec::new();
+    }
+    tokens.iter().map(ToString::to_string).collect()
+}
+
+/// Collect tokens, looking through the invisible groups that `macro_rules`
+/// wraps fragments like `$field:literal` in.
+fn flatten_tokens(tokens: TokenStream2, out: &mut Vec<TokenTree>) {
+    for token in tokens {
+        match token {
+            TokenTree::Group(group) if group.delimiter() == Delimiter::None => {
+                flatten_tokens(group.stream(), out)
+            }
+            token => out.push(token),
+        }
+    }
+}
+
+/// Call a setter method, used by the setter entries of `assign!`.
+///
+/// The input is `receiver, [prefix] field (value)`, which expands to
+/// `receiver.set_field(value)`, or to `receiver.prefixfield(value)` when a
+/// prefix is given.
+#[doc(hidden)]
+#[proc_macro]
+pub fn setter(input: TokenStream) -> TokenStream {
+    expand_setter(input.into())
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+fn expand_setter(input: TokenStream2) -> syn::Result<TokenStream2> {
+    let mut tokens = input.into_iter();
+    let receiver: TokenStream2 = tokens
+        .by_ref()
+        .take_while(|token| !matches!(token, TokenTree::Punct(punct) if punct.as_char() == ','))
+        .collect();
+
+    let prefix = match tokens.next() {
+        Some(TokenTree::Group(group)) if group.delimiter() == Delimiter::Bracket => {
+            match group.stream().into_iter().next() {
+                Some(TokenTree::Ident(prefix)) => prefix.to_string(),
+                _ => "set_".to_owned(),
+            }
+        }
+        _ => return Err(syn::Error::new_spanned(receiver, "malformed setter entry")),
+    };
+    let field = match tokens.next() {
+        Some(TokenTree::Ident(field)) => field,
+        _ => return Err(syn::Error::new_spanned(receiver, "malformed setter entry")),
+    };
+    let value = match tokens.next() {
+        Some(TokenTree::Group(value)) if value.delimiter() == Delimiter::Parenthesis => value,
+        _ => return Err(syn::Error::new_spanned(field, "malformed setter entry")),
+    };
+
+    let name = field.to_string();
+    let name = name.trim_start_matches("r#");
+    let span = Span::mixed_site().located_at(field.span());
+    let method = format_ident!("{}{}", prefix, name, span = span);
+    Ok(quote!(#receiver.#method #value))
+}
+
+fn expand_assign(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let fields = struct_fields(&input)?;
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let patch = match patch_name(&input)? {
+        Some(name) => expand_patch(&input, fields, name),
+        None => TokenStream2::new(),
+    };
+
+    let fields = fields.iter().enumerate().map(|(index, field)| {
+        let name = match &field.ident {
+            Some(ident) => ident.to_string().trim_start_matches("r#").to_owned(),
+            None => index.to_string(),
+        };
+        let ty = tokens_to_string(field.ty.to_token_stream());
+        let visibility = visibility(&field.vis);
+        quote! {
+            ::assign::Field::new(#name, #ty, #visibility)
+        }
+    });
+
+    Ok(quote! {
+        impl #impl_generics ::assign::Assign for #ident #ty_generics #where_clause {
+            const FIELDS: &'static [::assign::Field] = &[#(#fields),*];
+        }
+
+        #patch
+    })
+}
+
+fn patch_name(input: &DeriveInput) -> syn::Result<Option<Ident>> {
+    let mut name = None;
+    for attr in &input.attrs {
+        if attr.path().is_ident("assign") {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("patch") {
+                    name = Some(if meta.input.peek(Token![=]) {
+                        meta.value()?.parse()?
+                    } else {
+                        format_ident!("{}Patch", input.ident)
+                    });
+                    Ok(())
+                } else {
+                    Err(meta.error("expected `patch`"))
+                }
+            })?;
+        }
+    }
+    Ok(name)
+}
+
+fn expand_patch(input: &DeriveInput, fields: &Fields, patch: Ident) -> TokenStream2 {
+    let vis = &input.vis;
+    let ident = &input.ident;
+    let generics = &input.generics;
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let doc = format!(
+        "A patch of [`{}`], generated by `#[derive(Assign)]`.",
+        ident
+    );
+
+    let members: Vec<_> = fields.members().collect();
+    let field_vis = fields.iter().map(|field| &field.vis);
+    let field_ty = fields.iter().map(|field| &field.ty);
+    let name = patch.to_string();
+    let debug = match fields {
+        Fields::Named(_) => {
+            let names = fields.iter().map(|field| {
+                let name = field.ident.as_ref().unwrap().to_string();
+                name.trim_start_matches("r#").to_owned()
+            });
+            quote!(f.debug_struct(#name)#(.field(#names, &self.#members))*.finish())
+        }
+        Fields::Unnamed(_) => quote!(f.debug_tuple(#name)#(.field(&self.#members))*.finish()),
+        Fields::Unit => quote!(f.write_str(#name)),
+    };
+
+    // The bounds are higher-ranked so that a field type which doesn't
+    // implement the trait leaves the impl out instead of failing to compile.
+    let bounded = |bound: TokenStream2| {
+        let mut generics = generics.clone();
+        let where_clause = generics.make_where_clause();
+        for field in fields {
+            let ty = &field.ty;
+            where_clause
+                .predicates
+                .push(parse_quote!(for<'__assign> ::core::option::Option<#ty>: #bound));
+        }
+        generics.where_clause.into_token_stream()
+    };
+    let debug_where_clause = bounded(quote!(::core::fmt::Debug));
+    let clone_where_clause = bounded(quote!(::core::clone::Clone));
+    let definition = match fields {
+        Fields::Named(_) => quote! {
+            #vis struct #patch #generics #where_clause {
+                #(#field_vis #members: ::core::option::Option<#field_ty>,)*
+            }
+        },
+        Fields::Unnamed(_) => quote! {
+            #vis struct #patch #generics (
+                #(#field_vis ::core::option::Option<#field_ty>,)*
+            ) #where_clause;
+        },
+        Fields::Unit => quote! {
+            #vis struct #patch #generics #where_clause;
+        },
+    };
+
+    quote! {
+        #[doc = #doc]
+        #definition
+
+        impl #impl_generics ::core::default::Default for #patch #ty_generics #where_clause {
+            fn default() -> Self {
+                Self {
+                    #(#members: ::core::option::Option::None,)*
+                }
+            }
+        }
+
+        impl #impl_generics ::core::fmt::Debug for #patch #ty_generics #debug_where_clause {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                #debug
+            }
+        }
+
+        impl #impl_generics ::core::clone::Clone for #patch #ty_generics #clone_where_clause {
+            fn clone(&self) -> Self {
+                Self {
+                    #(#members: ::core::clone::Clone::clone(&self.#members),)*
+                }
+            }
+        }
+
+        impl #impl_generics ::assign::Patch for #patch #ty_generics #where_clause {
+            type Target = #ident #ty_generics;
+
+            #[allow(unused_variables)]
+            fn apply(self, target: &mut Self::Target) {
+                #(
+                    if let ::core::option::Option::Some(value) = self.#members {
+                        target.#members = value;
+                    }
+                )*
+            }
+
+            #[allow(unused_variables)]
+            fn then(self, next: Self) -> Self {
+                Self {
+                    #(#members: next.#members.or(self.#members),)*
+                }
+            }
+        }
+
+        impl #impl_generics ::assign::Patchable for #ident #ty_generics #where_clause {
+            type Patch = #patch #ty_generics;
+        }
+    }
+}
+
+fn struct_fields(input: &DeriveInput) -> syn::Result<&Fields> {
+    match &input.data {
+        Data::Struct(data) => Ok(&data.fields),
+        Data::Enum(data) => Err(syn::Error::new_spanned(
+            data.enum_token,
+            "`#[derive(Assign)]` only supports structs",
+        )),
+        Data::Union(data) => Err(syn::Error::new_spanned(
+            data.union_token,
+            "`#[derive(Assign)]` only supports structs",
+        )),
+    }
+}
+
+fn visibility(vis: &Visibility) -> TokenStream2 {
+    match vis {
+        Visibility::Public(_) => quote!(::assign::Visibility::Public),
+        Visibility::Restricted(restricted) => {
+            let mut path = tokens_to_string(restricted.path.to_token_stream());
+            if restricted.in_token.is_some() {
+                path.insert_str(0, "in ");
+            }
+            quote!(::assign::Visibility::Restricted(#path))
+        }
+        Visibility::Inherited => quote!(::assign::Visibility::Inherited),
+    }
+}
+
+/// Render a type or path the way it is usually written, e.g.
+/// `&'a Vec<(u8, u8)>` rather than the `& 'a Vec < (u8 , u8) >` produced by
+/// `to_string`.
+fn tokens_to_string(tokens: TokenStream2) -> String {
+    let mut name = String::new();
+    write_tokens(tokens, &mut name);
+    name
+}
+
+fn write_tokens(tokens: TokenStream2, out: &mut String) {
+    let mut op = String::new();
+    for token in tokens {
+        match token {
+            TokenTree::Punct(punct) => {
+                op.push(punct.as_char());
+                if punct.spacing() == Spacing::Joint && punct.as_char() != '\'' {
+                    continue;
+                }
+                match op.as_str() {
+                    "," | ";" => {
+                        out.push_str(&op);
+                        out.push(' ');
+                    }
+                    "->" | "=" | "+" => {
+                        out.push(' ');
+                        out.push_str(&op);
+                        out.push(' ');
+                    }
+                    _ => out.push_str(&op),
+                }
+                op.clear();
+            }
+            TokenTree::Ident(ident) => {
+                if out.ends_with(|c: char| c.is_alphanumeric() || c == '_') {
+                    out.push(' ');
+                }
+                out.push_str(&ident.to_string());
+            }
+            TokenTree::Literal(literal) => out.push_str(&literal.to_string()),
+            TokenTree::Group(group) => {
+                let (open, close) = match group.delimiter() {
+                    Delimiter::Parenthesis => ("(", ")"),
+                    Delimiter::Bracket => ("[", "]"),
+                    Delimiter::Brace => ("{", "}"),
+                    Delimiter::None => ("", ""),
+                };
+                out.push_str(open);
+                write_tokens(group.stream(), out);
+                out.push_str(close);
+            }
+        }
+    }
+}
@@ -0,0 +1,123 @@
+use assign::{patch, Assign, Patch, Visibility};
+
+mod config {
+    use assign::Assign;
+
+    #[allow(dead_code)]
+    #[derive(Assign)]
+    #[assign(patch = ConfigOverrides)]
+    pub struct Config<T: Clone> {
+        pub name: &'static str,
+        pub(super) values: Vec<T>,
+        pub(in crate::config) limit: Option<(u8, [u8; 2])>,
+        internal: Box<dyn Fn(&mut T) -> bool + Send>,
+    }
+
+    // Not clashing with a generated patch, which is named differently.
+    #[allow(dead_code)]
+    pub struct ConfigPatch;
+}
+
+#[derive(Assign)]
+#[assign(patch)]
+struct Unit;
+
+#[allow(dead_code)]
+#[derive(Assign)]
+#[assign(patch)]
+struct Pair(pub u8, String);
+
+// Without `#[assign(patch)]`, no patch is generated.
+#[allow(dead_code)]
+#[derive(Assign)]
+struct Empty;
+
+#[allow(dead_code)]
+struct EmptyPatch;
+
+#[test]
+fn named_fields() {
+    let fields = config::Config::<u8>::FIELDS;
+
+    assert_eq!(fields.len(), 4);
+    assert_eq!(fields[0].name, "name");
+    assert_eq!(fields[0].ty, "&'static str");
+    assert_eq!(fields[0].visibility, Visibility::Public);
+    assert_eq!(fields[1].ty, "Vec<T>");
+    assert_eq!(fields[1].visibility, Visibility::Restricted("super"));
+    assert_eq!(fields[2].ty, "Option<(u8, [u8; 2])>");
+    assert_eq!(
+        fields[2].visibility,
+        Visibility::Restricted("in crate::config")
+    );
+    assert_eq!(fields[3].ty, "Box<dyn Fn(&mut T) -> bool + Send>");
+    assert_eq!(fields[3].visibility, Visibility::Inherited);
+}
+
+#[allow(dead_code)]
+#[derive(Assign)]
+struct Raw {
+    r#type: u8,
+}
+
+#[test]
+fn raw_fields() {
+    assert_eq!(Raw::FIELDS[0].name, "type");
+    assert_eq!(Raw::field("type").map(|field| field.ty), Some("u8"));
+}
+
+#[test]
+fn positional_fields() {
+    assert!(Unit::FIELDS.is_empty());
+
+    assert_eq!(Pair::FIELDS[0].name, "0");
+    assert!(Pair::FIELDS[0].visibility.is_public());
+    assert_eq!(Pair::field("1").map(|field| field.ty), Some("String"));
+    assert_eq!(Pair::field("2"), None);
+}
+
+#[derive(Assign, Debug, Default, PartialEq)]
+#[assign(patch)]
+struct Options {
+    width: u32,
+    height: u32,
+    title: Option<&'static str>,
+}
+
+#[test]
+fn patches() {
+    let title = Some("editor");
+    let width: Option<u32> = None;
+    let first = patch!(Options, { width: 1, height: 2 });
+    let second = patch!(Options, {
+        width?,
+        height if title.is_some(): 3,
+        title: title,
+    });
+
+    assert_eq!(
+        format!("{:?}", first.clone()),
+        "OptionsPatch { width: Some(1), height: Some(2), title: None }"
+    );
+
+    let mut options = Options::default();
+    first.then(second).apply(&mut options);
+    assert_eq!(
+        options,
+        Options {
+            width: 1,
+            height: 3,
+            title: Some("editor"),
+        }
+    );
+
+    let mut pair = Pair(1, "a".into());
+    let patch = PairPatch(None, Some("b".into()));
+    assert_eq!(format!("{:?}", patch), r#"PairPatch(None, Some("b"))"#);
+    patch.apply(&mut pair);
+    assert_eq!((pair.0, pair.1.as_str()), (1, "b"));
+    UnitPatch.then(UnitPatch).apply(&mut Unit);
+
+    let overrides: config::ConfigOverrides<u8> = patch!(config::Config<u8>, { name: "config" });
+    assert_eq!(overrides.name, Some("config"));
+}
@@ -0,0 +1,60 @@
+use assign::{DynAssign, FieldError};
+
+#[derive(DynAssign, Debug, Default, PartialEq)]
+struct Entity<T> {
+    id: u64,
+    r#type: &'static str,
+    data: Option<T>,
+}
+
+#[derive(DynAssign, Default)]
+struct Pair(u8, String);
+
+#[test]
+fn set_fields() {
+    let mut entity = Entity::<Vec<u8>>::default();
+    assert_eq!(entity.field_names(), ["id", "type", "data"]);
+
+    entity.set_field("id", Box::new(7u64)).unwrap();
+    entity.set_field("type", Box::new("npc")).unwrap();
+    entity.set_field("data", Box::new(Some(vec![1u8]))).unwrap();
+    assert_eq!(
+        entity,
+        Entity {
+            id: 7,
+            r#type: "npc",
+            data: Some(vec![1]),
+        }
+    );
+
+    let mut pair = Pair::default();
+    pair.set_field("1", Box::new(String::from("b"))).unwrap();
+    assert_eq!(pair.1, "b");
+}
+
+#[test]
+fn field_errors() {
+    let mut entity = Entity::<u8>::default();
+
+    let error = entity.set_field("name", Box::new(1u64)).unwrap_err();
+    assert_eq!(error, FieldError::UnknownField("name".into()));
+    assert_eq!(error.to_string(), "unknown field `name`");
+
+    let error = entity.set_field("id", Box::new(1u32)).unwrap_err();
+    assert_eq!(
+        error,
+        FieldError::TypeMismatch {
+            field: "id",
+            expected: "u64",
+        }
+    );
+    assert_eq!(
+        error.to_string(),
+        "mismatched type for field `id`, expected `u64`"
+    );
+    assert_eq!(entity.id, 0);
+
+    let mut dynamic: Box<dyn DynAssign> = Box::new(Pair::default());
+    assert_eq!(dynamic.field_names(), ["0", "1"]);
+    assert!(dynamic.set_field("0", Box::new(1u8)).is_ok());
+}
@@ -0,0 +1,75 @@
+use assign::{ApplyPartial, PartialError};
+
+#[derive(ApplyPartial, Debug, PartialEq)]
+struct Config {
+    name: String,
+    tags: Vec<String>,
+    #[assign(nested)]
+    server: Server<u16>,
+}
+
+#[derive(ApplyPartial, Debug, PartialEq)]
+struct Server<P> {
+    host: String,
+    port: P,
+    r#type: Option<String>,
+}
+
+fn config() -> Config {
+    Config {
+        name: "app".into(),
+        tags: vec!["a".into()],
+        server: Server {
+            host: "localhost".into(),
+            port: 80,
+            r#type: None,
+        },
+    }
+}
+
+fn apply(config: &mut Config, json: &str) -> Result<(), PartialError<serde_json::Error>> {
+    config.apply_partial(&mut serde_json::Deserializer::from_str(json))
+}
+
+#[test]
+fn present_fields() {
+    let mut config = config();
+    apply(
+        &mut config,
+        r#"{ "tags": [], "server": { "port": 8080, "type": "http" } }"#,
+    )
+    .unwrap();
+
+    assert_eq!(config.name, "app");
+    assert!(config.tags.is_empty());
+    assert_eq!(config.server.host, "localhost");
+    assert_eq!(config.server.port, 8080);
+    assert_eq!(config.server.r#type.as_deref(), Some("http"));
+
+    apply(&mut config, "{}").unwrap();
+    assert_eq!(config.server.port, 8080);
+}
+
+#[test]
+fn field_errors() {
+    let mut config = config();
+
+    let error = apply(
+        &mut config,
+        r#"{ "name": "web", "server": { "port": -1 } }"#,
+    )
+    .unwrap_err();
+    assert_eq!(error.field, "server.port");
+    assert!(error.to_string().starts_with("server.port: invalid value"));
+    assert_eq!(config.name, "web");
+
+    let error = apply(&mut config, r#"{ "server": { "prot": 1 } }"#).unwrap_err();
+    assert_eq!(error.field, "server");
+    assert!(error.error.to_string().starts_with("unknown field `prot`"));
+
+    let error = apply(&mut config, r#"{ "tags": "a" }"#).unwrap_err();
+    assert_eq!(error.field, "tags");
+
+    let error = apply(&mut config, "[]").unwrap_err();
+    assert_eq!(error.field, "");
+}
@@ -0,0 +1,86 @@
+use alloc::boxed::Box;
+use alloc::string::String;
+use core::any::Any;
+use core::fmt;
+
+/// Assignment to the fields of a struct by their names at runtime.
+///
+/// This is usually implemented with `#[derive(DynAssign)]`, which requires the
+/// `derive` feature. Fields of tuple structs are named by their position.
+///
+#[cfg_attr(feature = "derive", doc = "```")]
+#[cfg_attr(not(feature = "derive"), doc = "```ignore")]
+/// use assign::{DynAssign, FieldError};
+///
+/// #[derive(DynAssign, Default)]
+/// struct Player {
+///     name: String,
+///     score: u32,
+/// }
+///
+/// let mut player = Player::default();
+/// assert_eq!(player.field_names(), ["name", "score"]);
+///
+/// player.set_field("score", Box::new(10u32)).unwrap();
+/// assert_eq!(player.score, 10);
+///
+/// assert_eq!(
+///     player.set_field("level", Box::new(2u8)),
+///     Err(FieldError::UnknownField("level".into())),
+/// );
+/// assert_eq!(
+///     player.set_field("score", Box::new("ten")),
+///     Err(FieldError::TypeMismatch { field: "score", expected: "u32" }),
+/// );
+/// ```
+pub trait DynAssign {
+    /// The names of the fields that can be assigned, in declaration order.
+    fn field_names(&self) -> &'static [&'static str];
+
+    /// Assign `value` to the field called `name`.
+    ///
+    /// The value must have exactly the type of the field.
+    fn set_field(&mut self, name: &str, value: Box<dyn Any>) -> Result<(), FieldError>;
+}
+
+/// An error produced by [`DynAssign::set_field`].
+#[non_exhaustive]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FieldError {
+    /// The struct has no field with the given name.
+    UnknownField(String),
+    /// The value does not have the type of the field.
+    TypeMismatch {
+        /// The name of the field.
+        field: &'static str,
+        /// The type of the field, as written in the struct definition.
+        expected: &'static str,
+    },
+}
+
+impl fmt::Display for FieldError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FieldError::UnknownField(name) => write!(f, "unknown field `{}`", name),
+            FieldError::TypeMismatch { field, expected } => {
+                write!(
+                    f,
+                    "mismatched type for field `{}`, expected `{}`",
+                    field, expected
+                )
+            }
+        }
+    }
+}
+
+/// Take the value of a field out of `value`, used by `#[derive(DynAssign)]`.
+pub fn downcast<T: Any>(
+    value: Box<dyn Any>,
+    field: &'static str,
+    expected: &'static str,
+) -> Result<T, FieldError> {
+    match value.downcast::<T>() {
+        Ok(value) => Ok(*value),
+        Err(_) => Err(FieldError::TypeMismatch { field, expected }),
+    }
+}
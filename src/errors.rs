@@ -0,0 +1,141 @@
+use core::fmt;
+
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+/// Number of errors kept by [`AssignErrors`] when the `alloc` feature is
+/// disabled.
+#[cfg(not(feature = "alloc"))]
+const CAPACITY: usize = 8;
+
+/// An error produced by the value of a single [`try_assign!`] entry.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AssignError<E> {
+    /// The path of the field the entry assigns, e.g. `"server.port"`.
+    pub field: &'static str,
+    /// The error the value evaluated to.
+    pub error: E,
+}
+
+impl<E: fmt::Display> fmt::Display for AssignError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.field, self.error)
+    }
+}
+
+/// The errors of all failing entries of a [`try_assign!`] invocation, in the
+/// order the entries are written.
+///
+/// With the `alloc` feature, every error is kept. Without it, only the first
+/// eight errors are kept and the number of further errors is available from
+/// [`truncated`](Self::truncated).
+pub struct AssignErrors<E> {
+    #[cfg(feature = "alloc")]
+    errors: Vec<AssignError<E>>,
+    #[cfg(not(feature = "alloc"))]
+    errors: [Option<AssignError<E>>; CAPACITY],
+    #[cfg(not(feature = "alloc"))]
+    len: usize,
+    #[cfg(not(feature = "alloc"))]
+    truncated: usize,
+}
+
+impl<E> AssignErrors<E> {
+    #[cfg(not(feature = "alloc"))]
+    const NONE: Option<AssignError<E>> = None;
+
+    #[doc(hidden)]
+    pub fn new() -> Self {
+        Self {
+            #[cfg(feature = "alloc")]
+            errors: Vec::new(),
+            #[cfg(not(feature = "alloc"))]
+            errors: [Self::NONE; CAPACITY],
+            #[cfg(not(feature = "alloc"))]
+            len: 0,
+            #[cfg(not(feature = "alloc"))]
+            truncated: 0,
+        }
+    }
+
+    #[doc(hidden)]
+    pub fn push(&mut self, field: &'static str, error: E) {
+        let error = AssignError { field, error };
+
+        #[cfg(feature = "alloc")]
+        self.errors.push(error);
+
+        #[cfg(not(feature = "alloc"))]
+        match self.errors.get_mut(self.len) {
+            Some(slot) => {
+                *slot = Some(error);
+                self.len += 1;
+            }
+            None => self.truncated += 1,
+        }
+    }
+
+    /// The number of errors kept.
+    pub fn len(&self) -> usize {
+        self.iter().count()
+    }
+
+    /// Whether no entry failed.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0 && self.truncated() == 0
+    }
+
+    /// The number of errors that were not kept because the list was full.
+    ///
+    /// This is always zero with the `alloc` feature.
+    pub fn truncated(&self) -> usize {
+        #[cfg(feature = "alloc")]
+        return 0;
+
+        #[cfg(not(feature = "alloc"))]
+        return self.truncated;
+    }
+
+    /// The error of the entry assigning `field`, if it failed.
+    pub fn get(&self, field: &str) -> Option<&E> {
+        self.iter()
+            .find(|error| error.field == field)
+            .map(|error| &error.error)
+    }
+
+    /// Iterate over the errors kept.
+    pub fn iter(&self) -> impl Iterator<Item = &AssignError<E>> {
+        #[cfg(feature = "alloc")]
+        return self.errors.iter();
+
+        #[cfg(not(feature = "alloc"))]
+        return self.errors.iter().flatten();
+    }
+}
+
+impl<E> Default for AssignErrors<E> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<E: fmt::Debug> fmt::Debug for AssignErrors<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<E: fmt::Display> fmt::Display for AssignErrors<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, error) in self.iter().enumerate() {
+            if index > 0 {
+                f.write_str("; ")?;
+            }
+            write!(f, "{}", error)?;
+        }
+        if self.truncated() > 0 {
+            write!(f, " (and {} more)", self.truncated())?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,66 @@
+use core::ops::IndexMut;
+
+#[cfg(feature = "alloc")]
+use alloc::collections::BTreeMap;
+#[cfg(feature = "std")]
+use core::hash::{BuildHasher, Hash};
+#[cfg(feature = "std")]
+use std::collections::HashMap;
+
+/// The collection an index entry is applied to.
+///
+/// Index entries call `Slot(&mut collection).assign_index(key, value)` with
+/// both [`InsertEntry`] and [`IndexEntry`] in scope. Method resolution prefers
+/// the by-value receiver of `InsertEntry`, so maps are inserted into, and
+/// falls back to the `&mut self` receiver of `IndexEntry` for everything
+/// implementing `IndexMut`.
+pub struct Slot<'a, C: ?Sized>(pub &'a mut C);
+
+pub trait InsertEntry<K, V> {
+    fn assign_index(self, key: K, value: V);
+}
+
+pub trait IndexEntry<K, V> {
+    fn assign_index(&mut self, key: K, value: V);
+}
+
+impl<C, K, V> IndexEntry<K, V> for Slot<'_, C>
+where
+    C: IndexMut<K> + ?Sized,
+    C::Output: AssignTo<V>,
+{
+    fn assign_index(&mut self, key: K, value: V) {
+        self.0[key].assign_to(value);
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<K: Ord, V> InsertEntry<K, V> for Slot<'_, BTreeMap<K, V>> {
+    fn assign_index(self, key: K, value: V) {
+        self.0.insert(key, value);
+    }
+}
+
+#[cfg(feature = "std")]
+impl<K: Eq + Hash, V, S: BuildHasher> InsertEntry<K, V> for Slot<'_, HashMap<K, V, S>> {
+    fn assign_index(self, key: K, value: V) {
+        self.0.insert(key, value);
+    }
+}
+
+/// Assignment to the output of `IndexMut`, which is a slice for ranges.
+pub trait AssignTo<V> {
+    fn assign_to(&mut self, value: V);
+}
+
+impl<T> AssignTo<T> for T {
+    fn assign_to(&mut self, value: T) {
+        *self = value;
+    }
+}
+
+impl<T: Clone, V: AsRef<[T]>> AssignTo<V> for [T] {
+    fn assign_to(&mut self, value: V) {
+        self.clone_from_slice(value.as_ref());
+    }
+}
@@ -22,8 +22,136 @@
 //! ```
 //!
 //! For details and examples, see the documentation for the macro itself.
+//!
+//! # Features
+//!
+//! - `derive`: Provides `#[derive(Assign)]`, which implements the [`Assign`]
+//!   trait describing the fields of a struct and, with `#[assign(patch)]`,
+//!   generates its [`Patch`] type. It also enables setter entries in
+//!   [`assign!`] and lets errors reported by the macros point at the offending
+//!   entry.
+//! - `alloc`: Lets [`AssignErrors`] keep any number of errors instead of a
+//!   fixed amount, lets index entries insert into `BTreeMap`s and provides
+//!   [`DynAssign`] to assign fields by name at runtime, [`assign_replace!`]
+//!   to keep the values replaced and transactions in [`try_assign!`].
+//!   Together with `derive`, it provides `#[derive(DynAssign)]`.
+//! - `std`: Enables `alloc` and lets index entries insert into `HashMap`s.
+//! - `serde`: Enables `alloc` and provides [`ApplyPartial`], which assigns the
+//!   fields present in a serialized partial document. Together with `derive`,
+//!   it provides `#[derive(ApplyPartial)]`.
 #![no_std]
 
+#[cfg(feature = "alloc")]
+extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
+
+#[cfg(feature = "alloc")]
+mod dynamic;
+mod errors;
+mod index;
+mod meta;
+#[cfg(feature = "serde")]
+mod partial;
+mod patch;
+#[cfg(feature = "alloc")]
+mod replace;
+mod tracked;
+mod variant;
+
+#[cfg(feature = "alloc")]
+pub use dynamic::{DynAssign, FieldError};
+pub use errors::{AssignError, AssignErrors};
+pub use meta::{Assign, Field, Visibility};
+#[cfg(feature = "serde")]
+pub use partial::{ApplyPartial, PartialError};
+pub use patch::{Patch, Patchable};
+#[cfg(feature = "alloc")]
+pub use replace::Replaced;
+pub use tracked::Changes;
+pub use variant::VariantMismatch;
+
+#[doc(hidden)]
+pub mod __private {
+    #[cfg(feature = "alloc")]
+    pub use crate::dynamic::downcast;
+    pub use crate::index::{IndexEntry, InsertEntry, Slot};
+    #[cfg(feature = "serde")]
+    pub use crate::partial::PartialSeed;
+    #[cfg(feature = "alloc")]
+    pub use crate::replace::Transaction;
+    pub use crate::tracked::{
+        same_type, Compare, CompareAny, CompareEq, Snapshot, SnapshotAny, SnapshotEq,
+    };
+    #[cfg(feature = "alloc")]
+    pub use alloc::boxed::Box;
+    #[cfg(feature = "serde")]
+    pub use alloc::vec::Vec;
+    #[cfg(feature = "serde")]
+    pub use serde;
+
+    pub use crate::__transaction as transaction;
+    #[cfg(not(feature = "derive"))]
+    pub use crate::{__error as error, __setter as setter, __unique as unique};
+    #[cfg(feature = "derive")]
+    pub use assign_derive::{error, setter, unique};
+}
+
+#[cfg(not(feature = "derive"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __error {
+    ([$($span:tt)*] note [$($other:tt)*] $note:tt $($message:tt)*) => {
+        compile_error!($($message)*)
+    };
+    ([$($span:tt)*] $($message:tt)*) => {
+        compile_error!($($message)*)
+    };
+}
+
+#[cfg(not(feature = "derive"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __unique {
+    ([$($seen:tt)+] [$($path:tt)*]) => {
+        $crate::assign!(@unique_path [$($seen)+] [$($path)*] [$($path)*] $);
+    };
+}
+
+#[cfg(not(feature = "derive"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __setter {
+    ($($tokens:tt)*) => {
+        compile_error!("setter entries require the `derive` feature of `assign`")
+    };
+}
+
+#[cfg(feature = "alloc")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __transaction {
+    ($target:expr) => {
+        $crate::__private::Transaction::new($target)
+    };
+}
+
+#[cfg(not(feature = "alloc"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __transaction {
+    ($target:expr) => {
+        compile_error!("transactions require the `alloc` feature of `assign`")
+    };
+}
+
+#[cfg(all(feature = "derive", feature = "serde"))]
+pub use assign_derive::ApplyPartial;
+#[cfg(feature = "derive")]
+pub use assign_derive::Assign;
+#[cfg(all(feature = "derive", feature = "alloc"))]
+pub use assign_derive::DynAssign;
+
 /// Mutate a struct value in a declarative style.
 ///
 /// # Basic usage
@@ -111,23 +239,1751 @@
 ///     help: Some("prints the version and quits.".into()),
 /// }));
 /// ```
+///
+/// # In-place mutation
+///
+/// When the first argument is written as `&mut target`, the entries are applied
+/// to `target` in place instead of to a moved value, and the mutable reference
+/// is returned. The target can be any place expression, such as a field of
+/// `self`, an element of a collection or a dereferenced mutable reference.
+///
+/// ```
+/// # use assign::assign;
+/// # use std::collections::HashMap;
+/// #
+/// #[derive(Default)]
+/// struct Limits {
+///     cpu: u32,
+///     memory: u32,
+/// }
+///
+/// struct Scheduler {
+///     defaults: Limits,
+///     slots: Vec<Limits>,
+///     per_user: HashMap<&'static str, Limits>,
+/// }
+///
+/// impl Scheduler {
+///     fn raise_defaults(&mut self) {
+///         assign!(&mut self.defaults, { cpu += 1, memory: 512 });
+///     }
+/// }
+///
+/// let mut scheduler = Scheduler {
+///     defaults: Limits::default(),
+///     slots: vec![Limits::default(), Limits::default()],
+///     per_user: HashMap::new(),
+/// };
+///
+/// scheduler.raise_defaults();
+/// assign!(&mut scheduler.slots[1], { cpu: 4 });
+/// let limits = assign!(&mut *scheduler.per_user.entry("root").or_default(), {
+///     cpu: 8,
+/// });
+/// limits.memory = 1024;
+///
+/// assert_eq!(scheduler.defaults.cpu, 1);
+/// assert_eq!(scheduler.slots[1].cpu, 4);
+/// assert_eq!(scheduler.per_user["root"].cpu, 8);
+/// assert_eq!(scheduler.per_user["root"].memory, 1024);
+/// ```
+///
+/// # Nested fields
+///
+/// A field of a field can be assigned by writing its path, separated with
+/// dots. The shorthand form takes the value from a local with the same name as
+/// the last segment of the path.
+///
+/// ```
+/// # use assign::assign;
+/// #
+/// #[derive(Default)]
+/// struct Config {
+///     name: String,
+///     server: Server,
+/// }
+///
+/// #[derive(Default)]
+/// struct Server {
+///     host: String,
+///     tls: Tls,
+/// }
+///
+/// #[derive(Default)]
+/// struct Tls {
+///     enabled: bool,
+///     port: u16,
+/// }
+///
+/// let port = 8443;
+/// let config = assign!(Config::default(), {
+///     name: "example".into(),
+///     server.host: "localhost".into(),
+///     server.tls.enabled: true,
+///     server.tls.port,
+/// });
+///
+/// assert_eq!(config.server.host, "localhost");
+/// assert!(config.server.tls.enabled);
+/// assert_eq!(config.server.tls.port, 8443);
+/// ```
+///
+/// Several fields of the same sub-struct can be grouped in a nested block,
+/// which mutates the existing value in place instead of replacing it. Blocks
+/// can be nested to any depth and accept the same entries as the top level.
+///
+/// ```
+/// # use assign::assign;
+/// #
+/// # #[derive(Default)]
+/// # struct Config { name: String, server: Server }
+/// # #[derive(Default)]
+/// # struct Server { host: String, tls: Tls }
+/// # #[derive(Default)]
+/// # struct Tls { enabled: bool, port: u16 }
+/// #
+/// let config = assign!(Config::default(), {
+///     server: {
+///         host: "localhost".into(),
+///         tls: {
+///             enabled: true,
+///             port: 8443,
+///         },
+///     },
+/// });
+///
+/// assert_eq!(config.server.host, "localhost");
+/// assert!(config.server.tls.enabled);
+/// assert_eq!(config.server.tls.port, 8443);
+/// ```
+///
+/// Because of this, a block expression used as a value has to be wrapped in
+/// parentheses, e.g. `field: ({ let x = 1; x + 1 })`.
+///
+/// Fields of tuple structs are assigned by their position, also as part of a
+/// path. Positional fields always need an explicit value.
+///
+/// ```
+/// # use assign::assign;
+/// #
+/// #[derive(Debug, Default, PartialEq)]
+/// struct Rgb(u8, u8, u8);
+///
+/// #[derive(Default)]
+/// struct Theme {
+///     colors: (Rgb, Rgb),
+/// }
+///
+/// let color = assign!(Rgb::default(), { 0: 255, 2: 128 });
+/// assert_eq!(color, Rgb(255, 0, 128));
+///
+/// let theme = assign!(Theme::default(), {
+///     colors.0: color,
+///     colors.1.1: 64,
+/// });
+/// assert_eq!(theme.colors, (Rgb(255, 0, 128), Rgb(0, 64, 0)));
+/// ```
+///
+/// # Compound assignment
+///
+/// Besides `:`, an entry can use any of Rust's compound assignment operators
+/// (`+=`, `-=`, `*=`, `/=`, `%=`, `&=`, `|=`, `^=`, `<<=` and `>>=`) to update
+/// the current value of a field. Entries are applied in the order they are
+/// written.
+///
+/// ```
+/// # use assign::assign;
+/// #
+/// #[derive(Default)]
+/// struct Request {
+///     retries: u32,
+///     budget: i64,
+///     flags: u8,
+/// }
+///
+/// let cost = 10;
+/// let request = assign!(Request::default(), {
+///     budget: 100,
+///     retries += 1,
+///     budget -= cost,
+///     flags |= 0b100,
+///     flags <<= 1,
+/// });
+///
+/// assert_eq!(request.retries, 1);
+/// assert_eq!(request.budget, 90);
+/// assert_eq!(request.flags, 0b1000);
+/// ```
+///
+/// # Update entries
+///
+/// A value written as a closure, like `count: |old| old + 1`, computes the new
+/// value of a field from its current one. The closure takes the current value
+/// by move and returns the new value. Annotating its parameter as `&mut`, as in
+/// `|tags: &mut _| tags.sort()`, edits the field in place instead.
+///
+/// ```
+/// # use assign::assign;
+/// #
+/// #[derive(Default)]
+/// struct Window {
+///     width: u32,
+///     title: Option<String>,
+///     tags: Vec<&'static str>,
+/// }
+///
+/// let window = assign!(Window::default(), {
+///     width: 2000,
+///     width: |width| width.clamp(320, 1920),
+///     title: |title| title.or(Some("untitled".into())),
+///     tags: |tags: &mut _| tags.extend(["resizable", "main"]),
+/// });
+///
+/// assert_eq!(window.width, 1920);
+/// assert_eq!(window.title.as_deref(), Some("untitled"));
+/// assert_eq!(window.tags, ["resizable", "main"]);
+/// ```
+///
+/// Taking the value by move needs the owned form of the macro, unless the
+/// field is `Copy`. To assign a closure to a field instead, wrap it in
+/// parentheses, as in `callback: (|event| handle(event))`.
+///
+/// # Naming the item
+///
+/// The value being assigned to is not accessible from the entries by default.
+/// Writing `|name|` before the block binds it to `name`, so that values can
+/// read the fields set by earlier entries. Each entry is a separate statement,
+/// so a value may borrow the item as long as the borrow ends with the entry.
+///
+/// ```
+/// # use assign::assign;
+/// #
+/// #[derive(Default)]
+/// struct Person {
+///     first: String,
+///     last: String,
+///     display_name: String,
+///     initials: [char; 2],
+/// }
+///
+/// let person = assign!(Person::default(), |p| {
+///     first: "Ada".into(),
+///     last: "Lovelace".into(),
+///     display_name: format!("{} {}", p.first, p.last),
+///     initials: [p.first.chars().next().unwrap(), p.last.chars().next().unwrap()],
+/// });
+///
+/// assert_eq!(person.display_name, "Ada Lovelace");
+/// assert_eq!(person.initials, ['A', 'L']);
+/// ```
+///
+/// # Method calls
+///
+/// An entry starting with a dot calls a method on a field instead of replacing
+/// it, which is useful to extend collections. The call is made in order with
+/// the other entries and its return value is discarded.
+///
+/// ```
+/// # use assign::assign;
+/// # use std::collections::HashMap;
+/// #
+/// #[derive(Default)]
+/// struct Job {
+///     name: String,
+///     tags: Vec<&'static str>,
+///     env: HashMap<String, String>,
+/// }
+///
+/// let defaults = vec![("LANG".to_string(), "C".to_string())];
+/// let job = assign!(Job::default(), {
+///     name: "build".into(),
+///     .tags.push("ci"),
+///     .tags.extend(["linux", "x86_64"]),
+///     .env.extend(defaults),
+///     .env.insert("CI".into(), "true".into()),
+/// });
+///
+/// assert_eq!(job.tags, ["ci", "linux", "x86_64"]);
+/// assert_eq!(job.env["LANG"], "C");
+/// assert_eq!(job.env["CI"], "true");
+/// ```
+///
+/// # Builder methods
+///
+/// An entry made of a single method call, like `.name("x")`, calls a builder
+/// method that takes the value by value and returns the updated one. This lets
+/// public fields and fluent builders be configured in the same block. A
+/// builder method returning a `Result` can be followed by `?` to propagate the
+/// error from the enclosing function. Builder methods need the owned form of
+/// the macro, since they move the value.
+///
+/// ```
+/// # use assign::assign;
+/// #
+/// #[derive(Debug, Default)]
+/// struct Listener {
+///     pub backlog: u32,
+///     host: String,
+///     port: u16,
+/// }
+///
+/// impl Listener {
+///     fn host(self, host: &str) -> Self {
+///         Self { host: host.into(), ..self }
+///     }
+///
+///     fn port(self, port: u16) -> Result<Self, String> {
+///         match port {
+///             0 => Err("port must not be zero".into()),
+///             port => Ok(Self { port, ..self }),
+///         }
+///     }
+/// }
+///
+/// fn bind(port: u16) -> Result<Listener, String> {
+///     Ok(assign!(Listener::default(), {
+///         backlog: 128,
+///         .host("localhost"),
+///         .port(port)?,
+///     }))
+/// }
+///
+/// let listener = bind(8080).unwrap();
+/// assert_eq!(listener.backlog, 128);
+/// assert_eq!(listener.host, "localhost");
+/// assert_eq!(listener.port, 8080);
+///
+/// assert!(bind(0).is_err());
+/// ```
+///
+/// # Setter methods
+///
+/// An entry starting with `set` calls a setter method instead of writing to
+/// the field, so `set name: value` becomes `item.set_name(value)`. A
+/// different prefix can be given in parentheses, like `set(with_) name`.
+/// Setter entries mix with all other entries, which helps with types that
+/// keep their fields private. Like plain entries, `set name` is a shorthand
+/// passing a local variable with the same name. Setter entries require the
+/// `derive` feature.
+///
+#[cfg_attr(feature = "derive", doc = "```")]
+#[cfg_attr(not(feature = "derive"), doc = "```ignore")]
+/// # use assign::assign;
+/// #
+/// mod widget {
+///     #[derive(Default)]
+///     pub struct Label {
+///         pub visible: bool,
+///         text: String,
+///         width: u32,
+///     }
+///
+///     impl Label {
+///         pub fn set_text(&mut self, text: &str) {
+///             self.text = text.into();
+///         }
+///
+///         pub fn with_width(&mut self, width: u32) -> &mut Self {
+///             self.width = width;
+///             self
+///         }
+///
+///         pub fn text(&self) -> &str {
+///             &self.text
+///         }
+///
+///         pub fn width(&self) -> u32 {
+///             self.width
+///         }
+///     }
+/// }
+///
+/// let width = 120;
+/// let label = assign!(widget::Label::default(), {
+///     visible: true,
+///     set text: "Hello",
+///     set(with_) width,
+/// });
+///
+/// assert!(label.visible);
+/// assert_eq!(label.text(), "Hello");
+/// assert_eq!(label.width(), 120);
+/// ```
+///
+/// # Index entries
+///
+/// Elements of collections are assigned by writing the index or key in
+/// brackets, either directly on the value being built or after a field. Any
+/// type implementing [`IndexMut`](core::ops::IndexMut) is supported, and with
+/// a range index the elements are cloned from a slice, array or `Vec`.
+/// Brackets can also appear in the middle of a path or before a nested block.
+///
+/// ```
+/// # use assign::assign;
+/// #
+/// #[derive(Default)]
+/// struct Frame {
+///     header: [u8; 4],
+///     payload: Vec<u8>,
+///     fields: Vec<Field>,
+/// }
+///
+/// #[derive(Clone, Default)]
+/// struct Field {
+///     name: &'static str,
+///     width: u8,
+/// }
+///
+/// let frame = assign!(Frame::default(), {
+///     header[0]: 0x7f,
+///     header[1..4]: *b"ELF",
+///     payload: vec![0; 8],
+///     payload[4..]: [1, 2, 3, 4],
+///     fields: vec![Field::default(); 2],
+///     fields[0].name: "len",
+///     fields[1]: { name: "crc", width: 4 },
+/// });
+///
+/// assert_eq!(&frame.header, b"\x7fELF");
+/// assert_eq!(frame.payload, [0, 0, 0, 0, 1, 2, 3, 4]);
+/// assert_eq!(frame.fields[1].width, 4);
+///
+/// let squares = assign!([0; 4], { [1]: 1, [2]: 4, [3]: 9 });
+/// assert_eq!(squares, [0, 1, 4, 9]);
+/// ```
+///
+/// Maps, which don't implement `IndexMut`, are inserted into instead. This is
+/// supported for `BTreeMap` with the `alloc` feature and for `HashMap` with the
+/// `std` feature.
+///
+#[cfg_attr(feature = "std", doc = "```")]
+#[cfg_attr(not(feature = "std"), doc = "```ignore")]
+/// # use assign::assign;
+/// # use std::collections::HashMap;
+/// #
+/// #[derive(Default)]
+/// struct Quota {
+///     limits: HashMap<&'static str, u32>,
+/// }
+///
+/// let quota = assign!(Quota::default(), {
+///     limits["cpu"]: 4,
+///     limits["memory"]: 1024,
+/// });
+/// assert_eq!(quota.limits["cpu"], 4);
+///
+/// let env = assign!(HashMap::new(), { ["HOME"]: "/root", ["SHELL"]: "sh" });
+/// assert_eq!(env["SHELL"], "sh");
+/// ```
+///
+/// # Conditional entries
+///
+/// An entry followed by `if` and a condition is only applied when the
+/// condition holds. Groups of entries can be made conditional with `if`,
+/// `else if` and `else` blocks, which accept the same entries as the top
+/// level, including `if let` patterns.
+///
+/// ```
+/// # use assign::assign;
+/// #
+/// #[derive(Default)]
+/// struct Options {
+///     verbose: bool,
+///     level: u8,
+///     output: Option<String>,
+///     color: bool,
+/// }
+///
+/// struct Args {
+///     debug: bool,
+///     quiet: bool,
+///     out: Option<String>,
+/// }
+///
+/// let args = Args { debug: true, quiet: false, out: Some("log.txt".into()) };
+/// let color = true;
+/// let options = assign!(Options::default(), {
+///     verbose if args.debug: true,
+///     color if !args.quiet,
+///     if args.quiet {
+///         level: 0,
+///     } else if args.debug {
+///         level: 3,
+///     } else {
+///         level: 1,
+///     },
+///     if let Some(out) = args.out {
+///         output: Some(out),
+///         color: false,
+///     },
+/// });
+///
+/// assert!(options.verbose);
+/// assert_eq!(options.level, 3);
+/// assert_eq!(options.output.as_deref(), Some("log.txt"));
+/// assert!(!options.color);
+/// ```
+///
+/// # Optional values
+///
+/// An entry written as `field?: value` expects an `Option` and only assigns
+/// the contained value when it is `Some`, leaving the field untouched
+/// otherwise. Like with plain entries, `field?` is a shorthand that reads a
+/// local `Option` with the same name as the field.
+///
+/// ```
+/// # use assign::assign;
+/// #
+/// #[derive(Default)]
+/// struct Settings {
+///     width: u32,
+///     height: u32,
+///     title: String,
+/// }
+///
+/// // Overrides supplied by the user.
+/// let width = Some(800);
+/// let height: Option<u32> = None;
+/// let title: Option<&str> = Some("editor");
+///
+/// let settings = assign!(Settings { width: 640, height: 480, title: "".into() }, {
+///     width?,
+///     height?,
+///     title?: title.map(String::from),
+/// });
+///
+/// assert_eq!(settings.width, 800);
+/// assert_eq!(settings.height, 480);
+/// assert_eq!(settings.title, "editor");
+/// ```
+///
+/// # Duplicate entries
+///
+/// Assigning the same field or field path twice in one block is rejected,
+/// since the first value would be lost. So is assigning a field after one it
+/// contains, or the other way around, as in `limits.connections: 10` followed
+/// by `limits: Limits::default()`. This covers plain and shorthand entries.
+/// Conditional entries, optional values, index entries, compound assignments
+/// and update entries are not checked, and neither are entries in different
+/// blocks.
+///
+/// ```compile_fail
+/// # use assign::assign;
+/// #
+/// # #[derive(Default)]
+/// # struct Server { port: u16 }
+/// #
+/// let server = assign!(Server::default(), {
+///     port: 80,
+///     port: 8080, // error: field `port` is assigned more than once
+/// });
+/// ```
+///
+/// With the `derive` feature, the error points at the later entry and a second
+/// error points at the earlier one. Without it, a single error is reported for
+/// the whole invocation, naming the field.
+///
+/// An entry that intentionally replaces an earlier one is marked with
+/// `#[overwrite]`.
+///
+/// ```
+/// # use assign::assign;
+/// #
+/// # #[derive(Default)]
+/// # struct Server { port: u16 }
+/// #
+/// let server = assign!(Server::default(), {
+///     port: 80,
+///     #[overwrite]
+///     port: 8080,
+/// });
+/// assert_eq!(server.port, 8080);
+/// ```
+///
+/// # Enum variants
+///
+/// Naming a variant before the block, as in `assign!(value, Enum::Variant {
+/// ... })`, assigns fields inside that variant of an enum. Fields of tuple
+/// variants are named by their position. Every entry has to start with a field
+/// of the variant, so builder methods on the enum itself are not available.
+///
+/// ```
+/// # use assign::assign;
+/// #
+/// #[derive(Debug, PartialEq)]
+/// enum Shape {
+///     Circle { radius: u32, center: (i32, i32) },
+///     Rect(u32, u32),
+/// }
+///
+/// let mut shape = Shape::Circle { radius: 1, center: (0, 0) };
+/// assign!(&mut shape, Shape::Circle {
+///     radius: 5,
+///     center.1: 2,
+/// });
+/// assert_eq!(shape, Shape::Circle { radius: 5, center: (0, 2) });
+/// ```
+///
+/// If the value is a different variant, the macro panics. With `else ignore`
+/// the value is returned unchanged instead, and with `else error` the macro
+/// returns a `Result` whose error is a [`VariantMismatch`] holding the value.
+///
+/// ```
+/// # use assign::assign;
+/// #
+/// # #[derive(Debug, PartialEq)]
+/// # enum Shape {
+/// #     Circle { radius: u32, center: (i32, i32) },
+/// #     Rect(u32, u32),
+/// # }
+/// #
+/// let shape = assign!(Shape::Rect(1, 2), Shape::Circle { radius: 5 } else ignore);
+/// assert_eq!(shape, Shape::Rect(1, 2));
+///
+/// let error = assign!(shape, Shape::Circle { radius: 5 } else error).unwrap_err();
+/// assert_eq!(error.to_string(), "expected variant `Shape::Circle`");
+/// assert_eq!(error.into_inner(), Shape::Rect(1, 2));
+/// ```
 #[macro_export]
 macro_rules! assign {
-    ($initial_value:expr, {
-        $( $field:ident $( : $value:expr )? ),+ $(,)?
-    }) => ({
-        let mut item = $initial_value;
-        $( $crate::assign!(@assign item $field $( : $value )?); )+
-        item
+    (@entries $ctx:tt [$($prefix:tt)*] $seen:tt $(,)?) => {};
+    (@entries $ctx:tt [$($prefix:tt)*] [$flag:tt $($seen:tt)*] # [overwrite] $($rest:tt)*) => {
+        $crate::assign!(@entries $ctx [$($prefix)*] [(overwrite) $($seen)*] $($rest)*);
+    };
+    (@entries $ctx:tt [$($prefix:tt)*] $seen:tt if $($rest:tt)*) => {
+        $crate::assign!(@if $ctx [$($prefix)*] $seen [] [] $($rest)*);
+    };
+    (@entries $ctx:tt [$($prefix:tt)*] [$flag:tt $($seen:tt)*] set $(($setter:ident))? $field:ident : $value:expr $(, $($rest:tt)*)?) => {
+        $crate::assign!(@setter $ctx [$($prefix)*] [$($setter)?] $field $value);
+        $crate::assign!(@entries $ctx [$($prefix)*] [() $($seen)*] $($($rest)*)?);
+    };
+    (@entries $ctx:tt [$($prefix:tt)*] [$flag:tt $($seen:tt)*] set $(($setter:ident))? $field:ident $(, $($rest:tt)*)?) => {
+        $crate::assign!(@setter $ctx [$($prefix)*] [$($setter)?] $field $field);
+        $crate::assign!(@entries $ctx [$($prefix)*] [() $($seen)*] $($($rest)*)?);
+    };
+    (@entries $ctx:tt [$($prefix:tt)*] [$flag:tt $($seen:tt)*] . $method:ident ( $($args:tt)* ) $(, $($rest:tt)*)?) => {
+        $crate::assign!(@builder $ctx [$($prefix)*] $method ($($args)*));
+        $crate::assign!(@entries $ctx [$($prefix)*] [() $($seen)*] $($($rest)*)?);
+    };
+    (@entries $ctx:tt [$($prefix:tt)*] [$flag:tt $($seen:tt)*] . $method:ident ( $($args:tt)* ) ? $(, $($rest:tt)*)?) => {
+        $crate::assign!(@builder $ctx [$($prefix)*] $method ($($args)*) ?);
+        $crate::assign!(@entries $ctx [$($prefix)*] [() $($seen)*] $($($rest)*)?);
+    };
+    (@entries $ctx:tt [$($prefix:tt)*] $seen:tt . $field:tt $($rest:tt)*) => {
+        $crate::assign!(@method $ctx [$($prefix)*] $seen [.$field] $($rest)*);
+    };
+    (@entries $ctx:tt [$($prefix:tt)*] $seen:tt [$($key:tt)*] $($rest:tt)*) => {
+        $crate::assign!(@path $ctx [$($prefix)*] $seen [] [$($key)*] $($rest)*);
+    };
+    (@entries $ctx:tt [$($prefix:tt)*] $seen:tt $field:tt $(. $path:tt)* : | $($rest:tt)*) => {
+        $crate::assign!(@path $ctx [$($prefix)*] $seen [.$field $(.$path)*] : | $($rest)*);
+    };
+    (@entries $ctx:tt [$($prefix:tt)*] [$flag:tt $($seen:tt)*] $field:tt $(. $path:tt)* : { $($inner:tt)* } $(, $($rest:tt)*)?) => {
+        $crate::assign!(@entries $ctx [$($prefix)*.$field $(.$path)*] [()] $($inner)*);
+        $crate::assign!(@entries $ctx [$($prefix)*] [() $($seen)*] $($($rest)*)?);
+    };
+    // Simple entries are taken four at a time, so that long blocks don't hit
+    // the recursion limit. The first rules hand the entries before a nested
+    // block, an update closure or an index entry, which would otherwise be
+    // taken for plain values, to the rules for single entries.
+    (@entries $ctx:tt [$($prefix:tt)*] [$flag:tt $($seen:tt)*]
+        $f1:tt $(. $p1:tt)* $(: $v1:expr)?,
+        $f2:tt $(. $p2:tt)* : { $($inner:tt)* } $($rest:tt)*
+    ) => {
+        $crate::assign!(@simple $ctx [$($prefix)*] [$flag $($seen)*] [.$f1 $(.$p1)*] ($($v1)?));
+        $crate::assign!(@entries $ctx [$($prefix)*] [() $($seen)* [.$f1 $(.$p1)*]] $f2 $(.$p2)* : { $($inner)* } $($rest)*);
+    };
+    (@entries $ctx:tt [$($prefix:tt)*] [$flag:tt $($seen:tt)*]
+        $f1:tt $(. $p1:tt)* $(: $v1:expr)?,
+        $f2:tt $(. $p2:tt)* : | $($rest:tt)*
+    ) => {
+        $crate::assign!(@simple $ctx [$($prefix)*] [$flag $($seen)*] [.$f1 $(.$p1)*] ($($v1)?));
+        $crate::assign!(@entries $ctx [$($prefix)*] [() $($seen)* [.$f1 $(.$p1)*]] $f2 $(.$p2)* : | $($rest)*);
+    };
+    (@entries $ctx:tt [$($prefix:tt)*] [$flag:tt $($seen:tt)*]
+        $f1:tt $(. $p1:tt)* $(: $v1:expr)?,
+        [$($key:tt)*] $($rest:tt)*
+    ) => {
+        $crate::assign!(@simple $ctx [$($prefix)*] [$flag $($seen)*] [.$f1 $(.$p1)*] ($($v1)?));
+        $crate::assign!(@entries $ctx [$($prefix)*] [() $($seen)* [.$f1 $(.$p1)*]] [$($key)*] $($rest)*);
+    };
+    (@entries $ctx:tt [$($prefix:tt)*] [$flag:tt $($seen:tt)*]
+        $f1:tt $(. $p1:tt)* $(: $v1:expr)?,
+        $f2:tt $(. $p2:tt)* $(: $v2:expr)?,
+        $f3:tt $(. $p3:tt)* : { $($inner:tt)* } $($rest:tt)*
+    ) => {
+        $crate::assign!(@simple $ctx [$($prefix)*] [$flag $($seen)*] [.$f1 $(.$p1)*] ($($v1)?) [.$f2 $(.$p2)*] ($($v2)?));
+        $crate::assign!(@entries $ctx [$($prefix)*] [() $($seen)* [.$f1 $(.$p1)*] [.$f2 $(.$p2)*]] $f3 $(.$p3)* : { $($inner)* } $($rest)*);
+    };
+    (@entries $ctx:tt [$($prefix:tt)*] [$flag:tt $($seen:tt)*]
+        $f1:tt $(. $p1:tt)* $(: $v1:expr)?,
+        $f2:tt $(. $p2:tt)* $(: $v2:expr)?,
+        $f3:tt $(. $p3:tt)* : | $($rest:tt)*
+    ) => {
+        $crate::assign!(@simple $ctx [$($prefix)*] [$flag $($seen)*] [.$f1 $(.$p1)*] ($($v1)?) [.$f2 $(.$p2)*] ($($v2)?));
+        $crate::assign!(@entries $ctx [$($prefix)*] [() $($seen)* [.$f1 $(.$p1)*] [.$f2 $(.$p2)*]] $f3 $(.$p3)* : | $($rest)*);
+    };
+    (@entries $ctx:tt [$($prefix:tt)*] [$flag:tt $($seen:tt)*]
+        $f1:tt $(. $p1:tt)* $(: $v1:expr)?,
+        $f2:tt $(. $p2:tt)* $(: $v2:expr)?,
+        [$($key:tt)*] $($rest:tt)*
+    ) => {
+        $crate::assign!(@simple $ctx [$($prefix)*] [$flag $($seen)*] [.$f1 $(.$p1)*] ($($v1)?) [.$f2 $(.$p2)*] ($($v2)?));
+        $crate::assign!(@entries $ctx [$($prefix)*] [() $($seen)* [.$f1 $(.$p1)*] [.$f2 $(.$p2)*]] [$($key)*] $($rest)*);
+    };
+    (@entries $ctx:tt [$($prefix:tt)*] [$flag:tt $($seen:tt)*]
+        $f1:tt $(. $p1:tt)* $(: $v1:expr)?,
+        $f2:tt $(. $p2:tt)* $(: $v2:expr)?,
+        $f3:tt $(. $p3:tt)* $(: $v3:expr)?,
+        $f4:tt $(. $p4:tt)* : { $($inner:tt)* } $($rest:tt)*
+    ) => {
+        $crate::assign!(@simple $ctx [$($prefix)*] [$flag $($seen)*] [.$f1 $(.$p1)*] ($($v1)?) [.$f2 $(.$p2)*] ($($v2)?) [.$f3 $(.$p3)*] ($($v3)?));
+        $crate::assign!(@entries $ctx [$($prefix)*] [() $($seen)* [.$f1 $(.$p1)*] [.$f2 $(.$p2)*] [.$f3 $(.$p3)*]] $f4 $(.$p4)* : { $($inner)* } $($rest)*);
+    };
+    (@entries $ctx:tt [$($prefix:tt)*] [$flag:tt $($seen:tt)*]
+        $f1:tt $(. $p1:tt)* $(: $v1:expr)?,
+        $f2:tt $(. $p2:tt)* $(: $v2:expr)?,
+        $f3:tt $(. $p3:tt)* $(: $v3:expr)?,
+        $f4:tt $(. $p4:tt)* : | $($rest:tt)*
+    ) => {
+        $crate::assign!(@simple $ctx [$($prefix)*] [$flag $($seen)*] [.$f1 $(.$p1)*] ($($v1)?) [.$f2 $(.$p2)*] ($($v2)?) [.$f3 $(.$p3)*] ($($v3)?));
+        $crate::assign!(@entries $ctx [$($prefix)*] [() $($seen)* [.$f1 $(.$p1)*] [.$f2 $(.$p2)*] [.$f3 $(.$p3)*]] $f4 $(.$p4)* : | $($rest)*);
+    };
+    (@entries $ctx:tt [$($prefix:tt)*] [$flag:tt $($seen:tt)*]
+        $f1:tt $(. $p1:tt)* $(: $v1:expr)?,
+        $f2:tt $(. $p2:tt)* $(: $v2:expr)?,
+        $f3:tt $(. $p3:tt)* $(: $v3:expr)?,
+        [$($key:tt)*] $($rest:tt)*
+    ) => {
+        $crate::assign!(@simple $ctx [$($prefix)*] [$flag $($seen)*] [.$f1 $(.$p1)*] ($($v1)?) [.$f2 $(.$p2)*] ($($v2)?) [.$f3 $(.$p3)*] ($($v3)?));
+        $crate::assign!(@entries $ctx [$($prefix)*] [() $($seen)* [.$f1 $(.$p1)*] [.$f2 $(.$p2)*] [.$f3 $(.$p3)*]] [$($key)*] $($rest)*);
+    };
+    (@entries $ctx:tt [$($prefix:tt)*] [$flag:tt $($seen:tt)*]
+        $f1:tt $(. $p1:tt)* $(: $v1:expr)?,
+        $f2:tt $(. $p2:tt)* $(: $v2:expr)?,
+        $f3:tt $(. $p3:tt)* $(: $v3:expr)?,
+        $f4:tt $(. $p4:tt)* $(: $v4:expr)? $(, $($rest:tt)*)?
+    ) => {
+        $crate::assign!(@simple $ctx [$($prefix)*] [$flag $($seen)*] [.$f1 $(.$p1)*] ($($v1)?) [.$f2 $(.$p2)*] ($($v2)?) [.$f3 $(.$p3)*] ($($v3)?) [.$f4 $(.$p4)*] ($($v4)?));
+        $crate::assign!(@entries $ctx [$($prefix)*] [() $($seen)* [.$f1 $(.$p1)*] [.$f2 $(.$p2)*] [.$f3 $(.$p3)*] [.$f4 $(.$p4)*]] $($($rest)*)?);
+    };
+    (@entries $ctx:tt [$($prefix:tt)*] [$flag:tt $($seen:tt)*] $field:tt $(. $path:tt)* : $value:expr $(, $($rest:tt)*)?) => {
+        $crate::assign!(@simple $ctx [$($prefix)*] [$flag $($seen)*] [.$field $(.$path)*] ($value));
+        $crate::assign!(@entries $ctx [$($prefix)*] [() $($seen)* [.$field $(.$path)*]] $($($rest)*)?);
+    };
+    (@entries $ctx:tt [$($prefix:tt)*] [$flag:tt $($seen:tt)*] $field:tt $(. $path:tt)* $(, $($rest:tt)*)?) => {
+        $crate::assign!(@simple $ctx [$($prefix)*] [$flag $($seen)*] [.$field $(.$path)*] ());
+        $crate::assign!(@entries $ctx [$($prefix)*] [() $($seen)* [.$field $(.$path)*]] $($($rest)*)?);
+    };
+    (@entries $ctx:tt [$($prefix:tt)*] $seen:tt $field:tt $($rest:tt)*) => {
+        $crate::assign!(@path $ctx [$($prefix)*] $seen [.$field] $($rest)*);
+    };
+    (@simple $ctx:tt [$($prefix:tt)*] $seen:tt) => {};
+    (@simple $ctx:tt [$($prefix:tt)*] [$flag:tt $($seen:tt)*] [$($path:tt)*] () $($rest:tt)*) => {
+        $crate::assign!(@unique $flag [$($seen)*] [$($path)*]);
+        $crate::assign!(@shorthand $ctx [$($prefix)*] [] $($path)*);
+        $crate::assign!(@simple $ctx [$($prefix)*] [() $($seen)* [$($path)*]] $($rest)*);
+    };
+    (@simple $ctx:tt [$($prefix:tt)*] [$flag:tt $($seen:tt)*] [$($path:tt)*] ($value:expr) $($rest:tt)*) => {
+        $crate::assign!(@unique $flag [$($seen)*] [$($path)*]);
+        $crate::assign!(@assign $ctx [$($prefix)* $($path)*] $value);
+        $crate::assign!(@simple $ctx [$($prefix)*] [() $($seen)* [$($path)*]] $($rest)*);
+    };
+    (@path $ctx:tt [$($prefix:tt)*] $seen:tt [$($path:tt)*] . $field:tt $($rest:tt)*) => {
+        $crate::assign!(@path $ctx [$($prefix)*] $seen [$($path)*.$field] $($rest)*);
+    };
+    (@path $ctx:tt [$($prefix:tt)*] [$flag:tt $($seen:tt)*] [$($path:tt)*] [$($key:tt)*] : { $($inner:tt)* } $(, $($rest:tt)*)?) => {
+        $crate::assign!(@entries $ctx [$($prefix)* $($path)* [$($key)*]] [()] $($inner)*);
+        $crate::assign!(@entries $ctx [$($prefix)*] [() $($seen)*] $($($rest)*)?);
+    };
+    (@path $ctx:tt [$($prefix:tt)*] [$flag:tt $($seen:tt)*] [$($path:tt)*] [$($key:tt)*] : $value:expr $(, $($rest:tt)*)?) => {
+        $crate::assign!(@index $ctx [$($prefix)* $($path)*] [$($key)*] $value);
+        $crate::assign!(@entries $ctx [$($prefix)*] [() $($seen)*] $($($rest)*)?);
+    };
+    (@path $ctx:tt [$($prefix:tt)*] $seen:tt [$($path:tt)*] [$($key:tt)*] $($rest:tt)*) => {
+        $crate::assign!(@path $ctx [$($prefix)*] $seen [$($path)* [$($key)*]] $($rest)*);
+    };
+    (@path $ctx:tt [$($prefix:tt)*] [$flag:tt $($seen:tt)*] [$($path:tt)*] : { $($inner:tt)* } $(, $($rest:tt)*)?) => {
+        $crate::assign!(@entries $ctx [$($prefix)* $($path)*] [()] $($inner)*);
+        $crate::assign!(@entries $ctx [$($prefix)*] [() $($seen)*] $($($rest)*)?);
+    };
+    (@path $ctx:tt [$($prefix:tt)*] [$flag:tt $($seen:tt)*] [$($path:tt)*] : | $old:ident : &mut $ty:ty | $body:expr $(, $($rest:tt)*)?) => {
+        $crate::assign!(@update $ctx [$($prefix)* $($path)*] (| $old: &mut $ty | $body));
+        $crate::assign!(@entries $ctx [$($prefix)*] [() $($seen)*] $($($rest)*)?);
+    };
+    (@path $ctx:tt [$($prefix:tt)*] [$flag:tt $($seen:tt)*] [$($path:tt)*] : | $old:pat_param | $body:expr $(, $($rest:tt)*)?) => {
+        $crate::assign!(@update $ctx [$($prefix)* $($path)*] (| $old | $body));
+        $crate::assign!(@entries $ctx [$($prefix)*] [() $($seen)*] $($($rest)*)?);
+    };
+    (@path $ctx:tt [$($prefix:tt)*] [$flag:tt $($seen:tt)*] [$($path:tt)*] : $value:expr $(, $($rest:tt)*)?) => {
+        $crate::assign!(@unique $flag [$($seen)*] [$($path)*]);
+        $crate::assign!(@assign $ctx [$($prefix)* $($path)*] $value);
+        $crate::assign!(@entries $ctx [$($prefix)*] [() $($seen)* [$($path)*]] $($($rest)*)?);
+    };
+    (@path $ctx:tt [$($prefix:tt)*] [$flag:tt $($seen:tt)*] [$($path:tt)*] ? : $value:expr $(, $($rest:tt)*)?) => {
+        $crate::assign!(@optional $ctx [$($prefix)* $($path)*] $value);
+        $crate::assign!(@entries $ctx [$($prefix)*] [() $($seen)*] $($($rest)*)?);
+    };
+    (@path $ctx:tt [$($prefix:tt)*] [$flag:tt $($seen:tt)*] [$($path:tt)*] ? $(, $($rest:tt)*)?) => {
+        $crate::assign!(@shorthand $ctx [$($prefix)*] [?] $($path)*);
+        $crate::assign!(@entries $ctx [$($prefix)*] [() $($seen)*] $($($rest)*)?);
+    };
+    (@path $ctx:tt [$($prefix:tt)*] $seen:tt [$($path:tt)*] if $($rest:tt)*) => {
+        $crate::assign!(@guard $ctx [$($prefix)*] $seen [$($path)*] [] $($rest)*);
+    };
+    (@path $ctx:tt [$($prefix:tt)*] [$flag:tt $($seen:tt)*] [$($path:tt)*] $op:tt $value:expr $(, $($rest:tt)*)?) => {
+        $crate::assign!(@compound $ctx [$($prefix)* $($path)*] $op $value);
+        $crate::assign!(@entries $ctx [$($prefix)*] [() $($seen)*] $($($rest)*)?);
+    };
+    (@guard $ctx:tt [$($prefix:tt)*] [$flag:tt $($seen:tt)*] [$($path:tt)*] [$($cond:tt)*] : { $($inner:tt)* } $(, $($rest:tt)*)?) => {
+        if $($cond)* {
+            $crate::assign!(@path $ctx [$($prefix)*] [()] [] $($path)* : { $($inner)* });
+        }
+        $crate::assign!(@entries $ctx [$($prefix)*] [() $($seen)*] $($($rest)*)?);
+    };
+    (@guard $ctx:tt [$($prefix:tt)*] [$flag:tt $($seen:tt)*] [$($path:tt)*] [$($cond:tt)*] : | $old:ident : &mut $ty:ty | $body:expr $(, $($rest:tt)*)?) => {
+        if $($cond)* {
+            $crate::assign!(@path $ctx [$($prefix)*] [()] [] $($path)* : | $old: &mut $ty | $body);
+        }
+        $crate::assign!(@entries $ctx [$($prefix)*] [() $($seen)*] $($($rest)*)?);
+    };
+    (@guard $ctx:tt [$($prefix:tt)*] [$flag:tt $($seen:tt)*] [$($path:tt)*] [$($cond:tt)*] : | $old:pat_param | $body:expr $(, $($rest:tt)*)?) => {
+        if $($cond)* {
+            $crate::assign!(@path $ctx [$($prefix)*] [()] [] $($path)* : | $old | $body);
+        }
+        $crate::assign!(@entries $ctx [$($prefix)*] [() $($seen)*] $($($rest)*)?);
+    };
+    (@guard $ctx:tt [$($prefix:tt)*] [$flag:tt $($seen:tt)*] [$($path:tt)*] [$($cond:tt)*] : $value:expr $(, $($rest:tt)*)?) => {
+        if $($cond)* {
+            $crate::assign!(@path $ctx [$($prefix)*] [()] [] $($path)* : $value);
+        }
+        $crate::assign!(@entries $ctx [$($prefix)*] [() $($seen)*] $($($rest)*)?);
+    };
+    (@guard $ctx:tt [$($prefix:tt)*] [$flag:tt $($seen:tt)*] [$($path:tt)*] [$($cond:tt)*] $(, $($rest:tt)*)?) => {
+        if $($cond)* {
+            $crate::assign!(@shorthand $ctx [$($prefix)*] [] $($path)*);
+        }
+        $crate::assign!(@entries $ctx [$($prefix)*] [() $($seen)*] $($($rest)*)?);
+    };
+    (@guard $ctx:tt [$($prefix:tt)*] $seen:tt [$($path:tt)*] [$($cond:tt)*] $next:tt $($rest:tt)*) => {
+        $crate::assign!(@guard $ctx [$($prefix)*] $seen [$($path)*] [$($cond)* $next] $($rest)*);
+    };
+    (@if $ctx:tt [$($prefix:tt)*] $seen:tt [$($chain:tt)*] [$($cond:tt)*] { $($then:tt)* } else if $($rest:tt)*) => {
+        $crate::assign!(@if $ctx [$($prefix)*] $seen [
+            $($chain)*
+            if $($cond)* {
+                $crate::assign!(@entries $ctx [$($prefix)*] [()] $($then)*);
+            } else
+        ] [] $($rest)*);
+    };
+    (@if $ctx:tt [$($prefix:tt)*] [$flag:tt $($seen:tt)*] [$($chain:tt)*] [$($cond:tt)*] { $($then:tt)* } else { $($else:tt)* } $(, $($rest:tt)*)?) => {
+        $($chain)*
+        if $($cond)* {
+            $crate::assign!(@entries $ctx [$($prefix)*] [()] $($then)*);
+        } else {
+            $crate::assign!(@entries $ctx [$($prefix)*] [()] $($else)*);
+        }
+        $crate::assign!(@entries $ctx [$($prefix)*] [() $($seen)*] $($($rest)*)?);
+    };
+    (@if $ctx:tt [$($prefix:tt)*] [$flag:tt $($seen:tt)*] [$($chain:tt)*] [$($cond:tt)*] { $($then:tt)* } $(, $($rest:tt)*)?) => {
+        $($chain)*
+        if $($cond)* {
+            $crate::assign!(@entries $ctx [$($prefix)*] [()] $($then)*);
+        }
+        $crate::assign!(@entries $ctx [$($prefix)*] [() $($seen)*] $($($rest)*)?);
+    };
+    (@if $ctx:tt [$($prefix:tt)*] $seen:tt [$($chain:tt)*] [$($cond:tt)*] $next:tt $($rest:tt)*) => {
+        $crate::assign!(@if $ctx [$($prefix)*] $seen [$($chain)*] [$($cond)* $next] $($rest)*);
+    };
+    (@method $ctx:tt [$($prefix:tt)*] [$flag:tt $($seen:tt)*] [$($path:tt)*] . $method:ident ( $($args:tt)* ) $(, $($rest:tt)*)?) => {
+        $crate::assign!(@call $ctx [$($prefix)* $($path)*] $method ($($args)*));
+        $crate::assign!(@entries $ctx [$($prefix)*] [() $($seen)*] $($($rest)*)?);
+    };
+    (@method $ctx:tt [$($prefix:tt)*] $seen:tt [$($path:tt)*] . $field:tt $($rest:tt)*) => {
+        $crate::assign!(@method $ctx [$($prefix)*] $seen [$($path)*.$field] $($rest)*);
+    };
+    (@method $ctx:tt [$($prefix:tt)*] $seen:tt [$($path:tt)*] [$($key:tt)*] $($rest:tt)*) => {
+        $crate::assign!(@method $ctx [$($prefix)*] $seen [$($path)* [$($key)*]] $($rest)*);
+    };
+    (@unique () [$($seen:tt)+] [$($path:tt)*]) => {
+        $crate::__private::unique!([$($seen)+] [$($path)*]);
+    };
+    (@unique $flag:tt [$($seen:tt)*] [$($path:tt)*]) => {};
+    (@unique_path $seen:tt $path:tt [. $field:tt $($rest:tt)*] $d:tt) => {
+        $crate::assign!(@unique_path $seen $path [$($rest)*] $d);
+    };
+    (@unique_path $seen:tt $path:tt [[$($key:tt)*] $($rest:tt)*] $d:tt) => {};
+    (@unique_path [$([$($seen:tt)*])+] $path:tt [] $d:tt) => {
+        macro_rules! __assign_unique {
+            $(($($seen)*) => {
+                $crate::assign!(@duplicate [$($seen)*] $path);
+            };)+
+            $(($($seen)* $d($d rest:tt)+) => {
+                $crate::assign!(@overlap [$($seen)*] $path);
+            };)+
+            ($d($d tokens:tt)*) => {};
+        }
+        $crate::assign!(@unique_call $path);
+        $crate::assign!(@unique_contained [$([$($seen)*])+] $path $d);
+    };
+    (@unique_contained [$($seen:tt)+] [$($path:tt)*] $d:tt) => {
+        macro_rules! __assign_contained {
+            ($($path)* $d($d rest:tt)+) => {
+                $crate::assign!(@overlap [$($path)* $d($d rest)+] [$($path)*]);
+            };
+            ($d($d tokens:tt)*) => {};
+        }
+        $crate::assign!(@contained_call $($seen)+);
+    };
+    (@unique_call [$($path:tt)*]) => {
+        __assign_unique!($($path)*);
+    };
+    (@contained_call $([$($seen:tt)*])+) => {
+        $(__assign_contained!($($seen)*);)+
+    };
+    (@duplicate [. $($first:tt)*] [. $($second:tt)*]) => {
+        $crate::__private::error!([$($second)*] note [$($first)*] (
+            "field `",
+            stringify!($($first)*),
+            "` is first assigned here"
+        ) concat!(
+            "field `",
+            stringify!($($second)*),
+            "` is assigned more than once, mark the later entry with `#[overwrite]` if this is intended"
+        ));
+    };
+    (@overlap [. $($first:tt)*] [. $($second:tt)*]) => {
+        $crate::__private::error!([$($second)*] note [$($first)*] (
+            "field `",
+            stringify!($($first)*),
+            "` is assigned here"
+        ) concat!(
+            "field `",
+            stringify!($($second)*),
+            "` overlaps with `",
+            stringify!($($first)*),
+            "` assigned before, mark the later entry with `#[overwrite]` if this is intended"
+        ));
+    };
+    (@assign (variant $($ctx:tt)*) $($args:tt)*) => {
+        $crate::assign!(@variant_field [@assign] ($($ctx)*) $($args)*);
+    };
+    (@optional (variant $($ctx:tt)*) $($args:tt)*) => {
+        $crate::assign!(@variant_field [@optional] ($($ctx)*) $($args)*);
+    };
+    (@index (variant $($ctx:tt)*) $($args:tt)*) => {
+        $crate::assign!(@variant_field [@index] ($($ctx)*) $($args)*);
+    };
+    (@compound (variant $($ctx:tt)*) $($args:tt)*) => {
+        $crate::assign!(@variant_field [@compound] ($($ctx)*) $($args)*);
+    };
+    (@call (variant $($ctx:tt)*) $($args:tt)*) => {
+        $crate::assign!(@variant_field [@call] ($($ctx)*) $($args)*);
+    };
+    (@builder (variant $($ctx:tt)*) $($args:tt)*) => {
+        $crate::assign!(@variant_field [@builder] ($($ctx)*) $($args)*);
+    };
+    (@setter (variant $($ctx:tt)*) $($args:tt)*) => {
+        $crate::assign!(@variant_field [@setter] ($($ctx)*) $($args)*);
+    };
+    (@update (variant $($ctx:tt)*) $($args:tt)*) => {
+        $crate::assign!(@variant_field [@update] ($($ctx)*) $($args)*);
+    };
+    (@variant_field [$($leaf:tt)*] ([$($variant:tt)*] $item:tt) [. $field:tt $($place:tt)*] $($args:tt)*) => {
+        match &mut $item {
+            $($variant)* { $field: field, .. } => {
+                $crate::assign!($($leaf)* (plain (*field)) [$($place)*] $($args)*);
+            }
+            #[allow(unreachable_patterns)]
+            _ => {}
+        }
+    };
+    (@variant_field [$($leaf:tt)*] $ctx:tt [$($place:tt)*] $($args:tt)*) => {
+        $crate::__private::error!([$($place)* $($args)*] "entries of an enum variant must start with one of its fields");
+    };
+    (@assign (plain $item:tt) [$($place:tt)*] $value:expr) => {
+        $item $($place)* = $value;
+    };
+    (@assign (patch $item:tt) [. $field:tt] $value:expr) => {
+        $item.$field = ::core::option::Option::Some($value);
+    };
+    (@assign (patch $item:tt) [$($place:tt)*] $value:expr) => {
+        $crate::assign!(@unpatchable [$($place)*] "nested fields and index entries");
+    };
+    (@assign (replace $item:tt $replaced:tt) [$($place:tt)*] $value:expr) => {{
+        #[allow(clippy::mem_replace_option_with_some, clippy::mem_replace_with_default)]
+        let value = ::core::mem::replace(&mut $item $($place)*, $value);
+        $crate::assign!(@restore $replaced [$($place)*] [$($place)*] value);
+    }};
+    (@assign (tracked $item:tt $changes:ident) [$($place:tt)*] $value:expr) => {{
+        #[allow(unused_imports)]
+        use $crate::__private::{CompareAny as _, CompareEq as _};
+        let value = $crate::__private::same_type(&$item $($place)*, $value);
+        if $crate::__private::Compare(&$item $($place)*, &value).changed() {
+            $changes.mark($crate::assign!(@field [$($place)*]));
+        }
+        $item $($place)* = value;
+    }};
+    (@assign (txn $item:tt $errors:ident $replaced:tt) [$($place:tt)*] $value:expr) => {
+        match $value {
+            ::core::result::Result::Ok(value) => {
+                $crate::assign!(@assign (replace $item $replaced) [$($place)*] value)
+            }
+            ::core::result::Result::Err(error) => $crate::assign!(@error $errors [$($place)*] error),
+        }
+    };
+    (@assign (try $item:tt $errors:ident) [$($place:tt)*] $value:expr) => {
+        match $value {
+            ::core::result::Result::Ok(value) => $item $($place)* = value,
+            ::core::result::Result::Err(error) => $crate::assign!(@error $errors [$($place)*] error),
+        }
+    };
+    (@optional (plain $item:tt) [$($place:tt)*] $value:expr) => {
+        if let ::core::option::Option::Some(value) = $value {
+            $item $($place)* = value;
+        }
+    };
+    (@optional (patch $item:tt) [. $field:tt] $value:expr) => {
+        if let value @ ::core::option::Option::Some(_) = $value {
+            $item.$field = value;
+        }
+    };
+    (@optional (patch $item:tt) [$($place:tt)*] $value:expr) => {
+        $crate::assign!(@unpatchable [$($place)*] "nested fields and index entries");
+    };
+    (@optional (replace $item:tt $replaced:tt) [$($place:tt)*] $value:expr) => {
+        if let ::core::option::Option::Some(value) = $value {
+            $crate::assign!(@assign (replace $item $replaced) [$($place)*] value);
+        }
+    };
+    (@optional (tracked $item:tt $changes:ident) [$($place:tt)*] $value:expr) => {
+        if let ::core::option::Option::Some(value) = $value {
+            $crate::assign!(@assign (tracked $item $changes) [$($place)*] value);
+        }
+    };
+    (@optional (txn $item:tt $errors:ident $replaced:tt) [$($place:tt)*] $value:expr) => {
+        match $value {
+            ::core::result::Result::Ok(::core::option::Option::Some(value)) => {
+                $crate::assign!(@assign (replace $item $replaced) [$($place)*] value)
+            }
+            ::core::result::Result::Ok(::core::option::Option::None) => {}
+            ::core::result::Result::Err(error) => $crate::assign!(@error $errors [$($place)*] error),
+        }
+    };
+    (@optional (try $item:tt $errors:ident) [$($place:tt)*] $value:expr) => {
+        match $value {
+            ::core::result::Result::Ok(::core::option::Option::Some(value)) => $item $($place)* = value,
+            ::core::result::Result::Ok(::core::option::Option::None) => {}
+            ::core::result::Result::Err(error) => $crate::assign!(@error $errors [$($place)*] error),
+        }
+    };
+    (@index (patch $item:tt) [$($place:tt)*] [$($key:tt)*] $value:expr) => {
+        $crate::assign!(@unpatchable [$($place)* $($key)*] "nested fields and index entries");
+    };
+    (@index (replace $item:tt $replaced:tt) [$($place:tt)*] [$($key:tt)*] $value:expr) => {{
+        let value = ::core::clone::Clone::clone(&$item $($place)*);
+        $crate::assign!(@restore $replaced [$($place)* [$($key)*]] [$($place)*] value);
+        $crate::assign!(@index (plain $item) [$($place)*] [$($key)*] $value);
+    }};
+    (@index (plain $item:tt) [$($place:tt)*] [$($key:tt)*] $value:expr) => {{
+        #[allow(unused_imports)]
+        use $crate::__private::{IndexEntry as _, InsertEntry as _};
+        $crate::__private::Slot(&mut $item $($place)*).assign_index($($key)*, $value);
+    }};
+    (@index (tracked $item:tt $changes:ident) [$($place:tt)*] [$($key:tt)*] $value:expr) => {
+        $crate::assign!(@track $item $changes [$($place)* [$($key)*]] [$($place)*] {
+            $crate::assign!(@index (plain $item) [$($place)*] [$($key)*] $value);
+        });
+    };
+    (@index (txn $item:tt $errors:ident $replaced:tt) [$($place:tt)*] [$($key:tt)*] $value:expr) => {
+        match $value {
+            ::core::result::Result::Ok(value) => {
+                $crate::assign!(@index (replace $item $replaced) [$($place)*] [$($key)*] value)
+            }
+            ::core::result::Result::Err(error) => {
+                $crate::assign!(@error $errors [$($place)* [$($key)*]] error)
+            }
+        }
+    };
+    (@index (try $item:tt $errors:ident) [$($place:tt)*] [$($key:tt)*] $value:expr) => {
+        match $value {
+            ::core::result::Result::Ok(value) => {
+                $crate::assign!(@index (plain $item) [$($place)*] [$($key)*] value)
+            }
+            ::core::result::Result::Err(error) => {
+                $crate::assign!(@error $errors [$($place)* [$($key)*]] error)
+            }
+        }
+    };
+    (@compound (patch $item:tt) [$($place:tt)*] $op:tt $value:expr) => {
+        $crate::assign!(@unpatchable [$op] "compound assignments");
+    };
+    (@compound (replace $item:tt $replaced:tt) [$($place:tt)*] $op:tt $value:expr) => {{
+        let value = ::core::clone::Clone::clone(&$item $($place)*);
+        $crate::assign!(@restore $replaced [$($place)*] [$($place)*] value);
+        $crate::assign!(@compound (plain $item) [$($place)*] $op $value);
+    }};
+    (@compound (tracked $item:tt $changes:ident) [$($place:tt)*] $op:tt $value:expr) => {
+        $crate::assign!(@track $item $changes [$($place)*] [$($place)*] {
+            $crate::assign!(@compound (plain $item) [$($place)*] $op $value);
+        });
+    };
+    (@compound (txn $item:tt $errors:ident $replaced:tt) [$($place:tt)*] $op:tt $value:expr) => {
+        match $value {
+            ::core::result::Result::Ok(value) => {
+                $crate::assign!(@compound (replace $item $replaced) [$($place)*] $op value);
+            }
+            ::core::result::Result::Err(error) => $crate::assign!(@error $errors [$($place)*] error),
+        }
+    };
+    (@compound (try $item:tt $errors:ident) [$($place:tt)*] $op:tt $value:expr) => {
+        match $value {
+            ::core::result::Result::Ok(value) => {
+                $crate::assign!(@compound (plain $item) [$($place)*] $op value);
+            }
+            ::core::result::Result::Err(error) => $crate::assign!(@error $errors [$($place)*] error),
+        }
+    };
+    (@compound (plain $item:tt) [$($place:tt)*] += $value:expr) => { $item $($place)* += $value; };
+    (@compound (plain $item:tt) [$($place:tt)*] -= $value:expr) => { $item $($place)* -= $value; };
+    (@compound (plain $item:tt) [$($place:tt)*] *= $value:expr) => { $item $($place)* *= $value; };
+    (@compound (plain $item:tt) [$($place:tt)*] /= $value:expr) => { $item $($place)* /= $value; };
+    (@compound (plain $item:tt) [$($place:tt)*] %= $value:expr) => { $item $($place)* %= $value; };
+    (@compound (plain $item:tt) [$($place:tt)*] &= $value:expr) => { $item $($place)* &= $value; };
+    (@compound (plain $item:tt) [$($place:tt)*] |= $value:expr) => { $item $($place)* |= $value; };
+    (@compound (plain $item:tt) [$($place:tt)*] ^= $value:expr) => { $item $($place)* ^= $value; };
+    (@compound (plain $item:tt) [$($place:tt)*] <<= $value:expr) => { $item $($place)* <<= $value; };
+    (@compound (plain $item:tt) [$($place:tt)*] >>= $value:expr) => { $item $($place)* >>= $value; };
+    (@compound $ctx:tt [$($place:tt)*] $op:tt $value:expr) => {
+        $crate::__private::error!([$op] concat!(
+            "expected `:` or a compound assignment operator, found `",
+            stringify!($op),
+            "`"
+        ));
+    };
+    (@call (patch $item:tt) [$($place:tt)*] $method:ident ($($args:tt)*)) => {
+        $crate::assign!(@unpatchable [$method] "method calls");
+    };
+    (@call (replace $item:tt $replaced:tt) [$($place:tt)*] $method:ident ($($args:tt)*)) => {{
+        let value = ::core::clone::Clone::clone(&$item $($place)*);
+        $crate::assign!(@restore $replaced [$($place)*] [$($place)*] value);
+        $item $($place)*.$method($($args)*);
+    }};
+    (@call (tracked $item:tt $changes:ident) [$($place:tt)*] $method:ident ($($args:tt)*)) => {
+        $crate::assign!(@track $item $changes [$($place)*] [$($place)*] {
+            $item $($place)*.$method($($args)*);
+        });
+    };
+    (@call (txn $item:tt $errors:ident $replaced:tt) $($args:tt)*) => {
+        $crate::assign!(@call (replace $item $replaced) $($args)*);
+    };
+    (@call ($mode:ident $item:tt $($state:tt)*) [$($place:tt)*] $method:ident ($($args:tt)*)) => {
+        $item $($place)*.$method($($args)*);
+    };
+    (@builder (patch $item:tt) [$($place:tt)*] $method:ident ($($args:tt)*) $($try:tt)?) => {
+        $crate::assign!(@unpatchable [$method] "method calls");
+    };
+    (@builder (replace $item:tt $replaced:tt) [$($place:tt)*] $method:ident ($($args:tt)*) $($try:tt)?) => {
+        $crate::assign!(@unrestorable [$method] "builder methods");
+    };
+    (@builder (tracked $item:tt $changes:ident) [$($place:tt)*] $method:ident ($($args:tt)*) $($try:tt)?) => {
+        $crate::assign!(@track $item $changes [$($place)*] [$($place)*] {
+            $item $($place)* = $item $($place)*.$method($($args)*) $($try)?;
+        });
+    };
+    (@builder (txn $item:tt $errors:ident $replaced:tt) [$($place:tt)*] $method:ident ($($args:tt)*) $($try:tt)?) => {
+        $crate::assign!(@unrestorable [$method] "builder methods");
+    };
+    (@builder (try $item:tt $errors:ident) [$($place:tt)*] $method:ident ($($args:tt)*) ?) => {
+        $crate::__private::error!([$method] "builder methods can't be used with `?` in `try_assign!`, as the value they consume would be lost on error");
+    };
+    (@builder ($mode:ident $item:tt $($state:tt)*) [$($place:tt)*] $method:ident ($($args:tt)*) $($try:tt)?) => {
+        $item $($place)* = $item $($place)*.$method($($args)*) $($try)?;
+    };
+    (@setter (patch $item:tt) [$($place:tt)*] [$($setter:ident)?] $field:ident $value:expr) => {
+        $crate::assign!(@unpatchable [$field] "setter entries");
+    };
+    (@setter (replace $item:tt $replaced:tt) [$($place:tt)*] [$($setter:ident)?] $field:ident $value:expr) => {
+        $crate::assign!(@unrestorable [$field] "setter entries");
+    };
+    (@setter (tracked $item:tt $changes:ident) [$($place:tt)*] [$($setter:ident)?] $field:ident $value:expr) => {
+        $crate::__private::error!([$field] "`assign_tracked!` can't compare fields assigned by setter entries");
+    };
+    (@setter (txn $item:tt $errors:ident $replaced:tt) [$($place:tt)*] [$($setter:ident)?] $field:ident $value:expr) => {
+        $crate::assign!(@unrestorable [$field] "setter entries");
+    };
+    (@setter (plain $item:tt) [$($place:tt)*] [$($setter:ident)?] $field:ident $value:expr) => {
+        $crate::__private::setter!($item $($place)*, [$($setter)?] $field ($value));
+    };
+    (@setter (try $item:tt $errors:ident) [$($place:tt)*] [$($setter:ident)?] $field:ident $value:expr) => {
+        match $value {
+            ::core::result::Result::Ok(value) => {
+                $crate::__private::setter!($item $($place)*, [$($setter)?] $field (value));
+            }
+            ::core::result::Result::Err(error) => $crate::assign!(@error $errors [$($place)*.$field] error),
+        }
+    };
+    (@update (patch $item:tt) [$($place:tt)*] (| $old:tt $($update:tt)*)) => {
+        $crate::assign!(@unpatchable [$old] "update entries");
+    };
+    (@update (replace $item:tt $replaced:tt) [$($place:tt)*] $update:tt) => {{
+        let value = ::core::clone::Clone::clone(&$item $($place)*);
+        $crate::assign!(@restore $replaced [$($place)*] [$($place)*] value);
+        $crate::assign!(@update (plain $item) [$($place)*] $update);
+    }};
+    (@update (tracked $item:tt $changes:ident) [$($place:tt)*] $update:tt) => {
+        $crate::assign!(@track $item $changes [$($place)*] [$($place)*] {
+            $crate::assign!(@update (plain $item) [$($place)*] $update);
+        });
+    };
+    (@update (txn $item:tt $errors:ident $replaced:tt) [$($place:tt)*] $update:tt) => {{
+        let value = ::core::clone::Clone::clone(&$item $($place)*);
+        $crate::assign!(@restore $replaced [$($place)*] [$($place)*] value);
+        $crate::assign!(@update (try $item $errors) [$($place)*] $update);
+    }};
+    (@update (plain $item:tt) [$($place:tt)*] (| $old:ident : &mut $ty:ty | $body:expr)) => {{
+        let $old: &mut $ty = &mut $item $($place)*;
+        $body;
+    }};
+    (@update (plain $item:tt) [$($place:tt)*] (| $old:pat_param | $body:expr)) => {
+        $item $($place)* = {
+            let $old = $item $($place)*;
+            $body
+        };
+    };
+    (@update (try $item:tt $errors:ident) [$($place:tt)*] (| $old:ident : &mut $ty:ty | $body:expr)) => {
+        match {
+            let $old: &mut $ty = &mut $item $($place)*;
+            $body
+        } {
+            ::core::result::Result::Ok(_) => {}
+            ::core::result::Result::Err(error) => $crate::assign!(@error $errors [$($place)*] error),
+        }
+    };
+    (@update (try $item:tt $errors:ident) [$($place:tt)*] (| $old:pat_param | $body:expr)) => {
+        $crate::__private::error!([$old] "update entries of `try_assign!` take the field by `&mut`");
+    };
+    (@unpatchable [$($span:tt)*] $entries:literal) => {
+        $crate::__private::error!([$($span)*] concat!(
+            "patches only support assigning top-level fields, not ",
+            $entries
+        ));
+    };
+    (@unrestorable [$($span:tt)*] $entries:literal) => {
+        $crate::__private::error!([$($span)*] concat!(
+            "replaced values can't be restored for ",
+            $entries
+        ));
+    };
+    (@error $errors:ident [$($path:tt)*] $error:ident) => {
+        $errors.push($crate::assign!(@field [$($path)*]), ::core::convert::From::from($error))
+    };
+    (@restore $replaced:tt [$($path:tt)*] [$($place:tt)*] $value:ident) => {
+        $replaced.push($crate::assign!(@field [$($path)*]), move |target| {
+            (*target) $($place)* = $value;
+        });
+    };
+    (@track $item:tt $changes:ident [$($path:tt)*] [$($place:tt)*] { $($change:tt)* }) => {{
+        #[allow(unused_imports)]
+        use $crate::__private::{CompareAny as _, CompareEq as _, SnapshotAny as _, SnapshotEq as _};
+        let before = $crate::__private::Snapshot(&$item $($place)*).snapshot();
+        $($change)*
+        let changed = match &before {
+            ::core::option::Option::Some(before) => {
+                $crate::__private::Compare(before, &$item $($place)*).changed()
+            }
+            ::core::option::Option::None => true,
+        };
+        if changed {
+            $changes.mark($crate::assign!(@field [$($path)*]));
+        }
+    }};
+    (@field [. $($path:tt)*]) => {
+        stringify!($($path)*)
+    };
+    (@field [$($path:tt)*]) => {
+        stringify!($($path)*)
+    };
+    (@shorthand $ctx:tt [$($prefix:tt)*] [$($kind:tt)?] . $field:ident) => {
+        $crate::assign!(@path $ctx [$($prefix)*] [()] [.$field] $($kind)? : $field);
+    };
+    (@shorthand $ctx:tt [$($prefix:tt)*] [$($kind:tt)?] . $field:literal) => {
+        $crate::__private::error!([$field] concat!(
+            "positional field `",
+            stringify!($field),
+            "` needs an explicit value"
+        ));
+    };
+    (@shorthand $ctx:tt [$($prefix:tt)*] [$($kind:tt)?] . $field:tt $($path:tt)+) => {
+        $crate::assign!(@shorthand $ctx [$($prefix)*.$field] [$($kind)?] $($path)+);
+    };
+    (@shorthand $ctx:tt [$($prefix:tt)*] [$($kind:tt)?] [$($key:tt)*] $($path:tt)+) => {
+        $crate::assign!(@shorthand $ctx [$($prefix)* [$($key)*]] [$($kind)?] $($path)+);
+    };
+    (@variant [] [$($variant:tt)*] $item:tt $result:ident { $($entries:tt)* }) => {{
+        if !::core::matches!($item, $($variant)* { .. }) {
+            ::core::panic!(::core::concat!("expected variant `", ::core::stringify!($($variant)*), "`"));
+        }
+        $crate::assign!(@entries (variant [$($variant)*] $item) [] [()] $($entries)*);
+        $result
+    }};
+    (@variant [ignore] [$($variant:tt)*] $item:tt $result:ident { $($entries:tt)* }) => {{
+        if ::core::matches!($item, $($variant)* { .. }) {
+            $crate::assign!(@entries (variant [$($variant)*] $item) [] [()] $($entries)*);
+        }
+        $result
+    }};
+    (@variant [error] [$($variant:tt)*] $item:tt $result:ident { $($entries:tt)* }) => {{
+        if ::core::matches!($item, $($variant)* { .. }) {
+            $crate::assign!(@entries (variant [$($variant)*] $item) [] [()] $($entries)*);
+            ::core::result::Result::Ok($result)
+        } else {
+            ::core::result::Result::Err($crate::VariantMismatch::new($result, ::core::stringify!($($variant)*)))
+        }
+    }};
+    (@variant [$mismatch:tt] $($rest:tt)*) => {
+        $crate::__private::error!([$mismatch] concat!(
+            "expected `ignore` or `error` after `else`, found `",
+            stringify!($mismatch),
+            "`"
+        ));
+    };
+    (&mut $target:expr, {
+        $($entries:tt)+
+    }) => ({
+        let item = &mut $target;
+        $crate::assign!(@entries (plain (*item)) [] [()] $($entries)+);
+        item
+    });
+    ($initial_value:expr, {
+        $($entries:tt)+
+    }) => ({
+        let mut item = $initial_value;
+        $crate::assign!(@entries (plain item) [] [()] $($entries)+);
+        item
+    });
+    (&mut $target:expr, |$name:ident| {
+        $($entries:tt)+
+    }) => ({
+        let $name = &mut $target;
+        $crate::assign!(@entries (plain (*$name)) [] [()] $($entries)+);
+        $name
+    });
+    ($initial_value:expr, |$name:ident| {
+        $($entries:tt)+
+    }) => ({
+        let mut $name = $initial_value;
+        $crate::assign!(@entries (plain $name) [] [()] $($entries)+);
+        $name
+    });
+    (&mut $target:expr, $variant:path { $($entries:tt)* } $(else $mismatch:tt)?) => ({
+        let item = &mut $target;
+        $crate::assign!(@variant [$($mismatch)?] [$variant] (*item) item { $($entries)* })
+    });
+    ($initial_value:expr, $variant:path { $($entries:tt)* } $(else $mismatch:tt)?) => ({
+        let mut item = $initial_value;
+        $crate::assign!(@variant [$($mismatch)?] [$variant] item item { $($entries)* })
+    });
+}
+
+/// Mutate a struct value in a declarative style, with fallible values.
+///
+/// Every value is a `Result` of what [`assign!`] would take. Entries whose
+/// value is `Ok` are applied, and instead of stopping at the first `Err`, all
+/// entries are evaluated and the errors are collected into [`AssignErrors`],
+/// together with the path of the field they were meant for. Like with `?`,
+/// errors are converted with [`From`] into the error type of the list.
+///
+/// ```
+/// # use assign::{try_assign, AssignErrors};
+/// # use std::num::ParseIntError;
+/// #
+/// #[derive(Debug, Default)]
+/// struct Request {
+///     id: u64,
+///     page: Page,
+/// }
+///
+/// #[derive(Debug, Default)]
+/// struct Page {
+///     number: u32,
+///     size: u8,
+/// }
+///
+/// fn parse(id: &str, number: &str, size: &str) -> Result<Request, AssignErrors<ParseIntError>> {
+///     try_assign!(Request::default(), {
+///         id: id.parse(),
+///         page: {
+///             number: number.parse(),
+///             size: size.parse(),
+///         },
+///     })
+/// }
+///
+/// let request = parse("42", "3", "20").unwrap();
+/// assert_eq!(request.id, 42);
+/// assert_eq!(request.page.size, 20);
+///
+/// let errors = parse("42", "three", "300").unwrap_err();
+/// let fields: Vec<_> = errors.iter().map(|error| error.field).collect();
+/// assert_eq!(fields, ["page.number", "page.size"]);
+/// assert_eq!(
+///     errors.to_string(),
+///     "page.number: invalid digit found in string; \
+///      page.size: number too large to fit in target type",
+/// );
+/// ```
+///
+/// Method calls are made as with [`assign!`], except that builder methods can't
+/// be used with `?`, as the value they consume would be lost on error.
+/// Compound assignment and optional entries take a `Result` of their usual
+/// value as well. Update entries have to take the field by `&mut`, and their
+/// closure returns a `Result` whose `Ok` value is ignored.
+///
+/// Like [`assign!`], `try_assign!(&mut target, { ... })` updates a value in
+/// place and returns `Result<&mut T, AssignErrors<E>>`. Entries that succeed
+/// are applied even if others fail.
+///
+/// # Transactions
+///
+/// `try_assign!(&mut target, transaction { ... })` either applies all entries
+/// or none of them. Each field is written as its entry is evaluated, and the
+/// replaced values are kept like with [`assign_replace!`]. If any entry fails,
+/// or panics partway, every field written so far gets its previous value back,
+/// so the target is left untouched without requiring it to be `Clone`.
+///
+/// Transactions take the same entries as `try_assign!`, except setter entries
+/// and builder methods. Entries that change a field in place, i.e. index
+/// entries, compound assignments, method calls and update entries, clone it
+/// first, so the field has to implement `Clone`. Transactions require the
+/// `alloc` feature.
+///
+#[cfg_attr(feature = "alloc", doc = "```")]
+#[cfg_attr(not(feature = "alloc"), doc = "```ignore")]
+/// # use assign::{try_assign, AssignErrors};
+/// # use std::num::ParseIntError;
+/// #
+/// #[derive(Debug, PartialEq)]
+/// struct Limits {
+///     connections: u32,
+///     timeout: u64,
+/// }
+///
+/// fn update(limits: &mut Limits, connections: &str, timeout: &str) -> Result<(), AssignErrors<ParseIntError>> {
+///     try_assign!(&mut *limits, transaction {
+///         connections: connections.parse(),
+///         timeout: timeout.parse(),
+///     })?;
+///     Ok(())
+/// }
+///
+/// let mut limits = Limits { connections: 10, timeout: 30 };
+/// assert!(update(&mut limits, "20", "soon").is_err());
+/// assert_eq!(limits, Limits { connections: 10, timeout: 30 });
+///
+/// update(&mut limits, "20", "60").unwrap();
+/// assert_eq!(limits, Limits { connections: 20, timeout: 60 });
+/// ```
+#[macro_export]
+macro_rules! try_assign {
+    (&mut $target:expr, transaction {
+        $($entries:tt)+
+    }) => ({
+        let item = &mut $target;
+        let mut errors = $crate::AssignErrors::new();
+        {
+            let mut txn = $crate::__private::transaction!(&mut *item);
+            $crate::assign!(@entries (txn (*txn.target) errors (txn.replaced)) [] [()] $($entries)+);
+            if errors.is_empty() {
+                txn.commit();
+            }
+        }
+        if errors.is_empty() {
+            ::core::result::Result::Ok(item)
+        } else {
+            ::core::result::Result::Err(errors)
+        }
+    });
+    (&mut $target:expr, {
+        $($entries:tt)+
+    }) => ({
+        let item = &mut $target;
+        let mut errors = $crate::AssignErrors::new();
+        $crate::assign!(@entries (try (*item) errors) [] [()] $($entries)+);
+        if errors.is_empty() {
+            ::core::result::Result::Ok(item)
+        } else {
+            ::core::result::Result::Err(errors)
+        }
+    });
+    ($initial_value:expr, {
+        $($entries:tt)+
+    }) => ({
+        let mut item = $initial_value;
+        let mut errors = $crate::AssignErrors::new();
+        $crate::assign!(@entries (try item errors) [] [()] $($entries)+);
+        if errors.is_empty() {
+            ::core::result::Result::Ok(item)
+        } else {
+            ::core::result::Result::Err(errors)
+        }
     });
-    (@assign $item:ident $field:ident : $value:expr) => {
-        $item.$field = $value;
+}
+
+/// Mutate a new value of a type in a declarative style.
+///
+/// `assign_default!(T, { ... })` is shorthand for
+/// `assign!(T::default(), { ... })`, so the type is named once at the start of
+/// the invocation. It takes the same entries as [`assign!`]. Generic types can
+/// be written with or without turbofish.
+///
+/// ```
+/// # use assign::assign_default;
+/// #[derive(Default)]
+/// struct Server {
+///     host: String,
+///     port: u16,
+///     tls: bool,
+/// }
+///
+/// let server = assign_default!(Server, {
+///     host: "example.com".into(),
+///     port: 443,
+/// });
+/// assert_eq!(server.port, 443);
+/// assert!(!server.tls);
+///
+/// #[derive(Default)]
+/// struct Range<T> {
+///     start: T,
+///     end: T,
+/// }
+///
+/// let range = assign_default!(Range::<u8>, { end: 10 });
+/// assert_eq!(range.start..range.end, 0..10);
+/// ```
+///
+/// To start from an associated constructor instead of [`Default`], name it
+/// after `=>`, with its arguments if it takes any:
+///
+/// ```
+/// # use assign::assign_default;
+/// struct Buffer {
+///     data: Vec<u8>,
+///     flushed: bool,
+/// }
+///
+/// impl Buffer {
+///     fn with_capacity(capacity: usize) -> Self {
+///         Self { data: Vec::with_capacity(capacity), flushed: true }
+///     }
+/// }
+///
+/// let buffer = assign_default!(Buffer => with_capacity(64), {
+///     .data.push(1),
+///     flushed: false,
+/// });
+/// assert!(buffer.data.capacity() >= 64);
+/// ```
+#[macro_export]
+macro_rules! assign_default {
+    ($type:ty => $constructor:ident $(($($args:expr),* $(,)?))?, {
+        $($entries:tt)+
+    }) => {
+        $crate::assign!(<$type>::$constructor($($($args),*)?), {
+            $($entries)+
+        })
     };
-    (@assign $item:ident $field:ident) => {
-        $item.$field = $field;
+    ($type:ty, {
+        $($entries:tt)+
+    }) => {
+        $crate::assign!(<$type as ::core::default::Default>::default(), {
+            $($entries)+
+        })
     };
 }
 
+/// Mutate a struct value in a declarative style, keeping the values replaced.
+///
+/// `assign_replace!(value, { ... })` assigns like [`assign!`] and returns the
+/// updated value together with the previous values of the changed fields, as
+/// [`Replaced`]. They can be written back with [`Replaced::restore`], which
+/// makes temporary overrides easy to undo.
+/// `assign_replace!(&mut target, { ... })` updates a value in place and only
+/// returns the replaced values.
+///
+/// All entries of [`assign!`] are supported except setter entries and builder
+/// methods. Entries that change a field in place, i.e. index entries, compound
+/// assignments, method calls and update entries, clone it first, so the field
+/// has to implement `Clone`. This macro requires the `alloc` feature.
+///
+/// ```
+/// # use assign::assign_replace;
+/// #
+/// #[derive(Debug, PartialEq)]
+/// struct Config {
+///     verbose: bool,
+///     retries: u32,
+///     server: Server,
+/// }
+///
+/// #[derive(Debug, PartialEq)]
+/// struct Server {
+///     port: u16,
+/// }
+///
+/// let mut config = Config { verbose: false, retries: 3, server: Server { port: 80 } };
+///
+/// let retries = 0;
+/// let replaced = assign_replace!(&mut config, {
+///     verbose: true,
+///     retries,
+///     server.port: 8080,
+/// });
+/// assert_eq!(replaced.fields().collect::<Vec<_>>(), ["verbose", "retries", "server.port"]);
+/// assert_eq!(config.server.port, 8080);
+///
+/// replaced.restore(&mut config);
+/// assert_eq!(config, Config { verbose: false, retries: 3, server: Server { port: 80 } });
+/// ```
+#[cfg(feature = "alloc")]
+#[macro_export]
+macro_rules! assign_replace {
+    (&mut $target:expr, {
+        $($entries:tt)+
+    }) => ({
+        let item = &mut $target;
+        let mut replaced = $crate::Replaced::new(&*item);
+        $crate::assign!(@entries (replace (*item) replaced) [] [()] $($entries)+);
+        replaced
+    });
+    ($initial_value:expr, {
+        $($entries:tt)+
+    }) => ({
+        let mut item = $initial_value;
+        let mut replaced = $crate::Replaced::new(&item);
+        $crate::assign!(@entries (replace item replaced) [] [()] $($entries)+);
+        (item, replaced)
+    });
+}
+
+/// Mutate a struct value in a declarative style, tracking which fields change.
+///
+/// `assign_tracked!(value, { ... })` assigns like [`assign!`] and returns the
+/// updated value together with the [`Changes`] it made. Each new value is
+/// compared with the current value of its field, and the field is reported as
+/// changed if they differ. Fields whose type does not implement `PartialEq`
+/// are reported as changed whenever they are assigned.
+/// `assign_tracked!(&mut target, { ... })` updates a value in place and only
+/// returns the changes.
+///
+/// All entries of [`assign!`] are supported except setter entries. Entries
+/// that change a field in place, i.e. index entries, compound assignments,
+/// method calls and update entries, compare it with a clone taken before, so
+/// fields that don't implement both `Clone` and `PartialEq` are reported as
+/// changed by them. Index entries are reported with their key, e.g.
+/// `"cells[0]"`. A field assigned again with `#[overwrite]` is reported if any
+/// of its entries changed it, even if a later one set it back.
+///
+/// ```
+/// # use assign::assign_tracked;
+/// #
+/// #[derive(Default)]
+/// struct Config {
+///     theme: String,
+///     font_size: u8,
+///     window: Window,
+/// }
+///
+/// #[derive(Default)]
+/// struct Window {
+///     width: u32,
+///     height: u32,
+/// }
+///
+/// let mut config = Config {
+///     font_size: 12,
+///     window: Window { width: 800, height: 600 },
+///     ..Default::default()
+/// };
+///
+/// let changes = assign_tracked!(&mut config, {
+///     theme: "dark".into(),
+///     font_size: 12,
+///     window.width: 1024,
+///     window.height: 600,
+/// });
+///
+/// assert!(changes.contains("window.width"));
+/// assert!(!changes.contains("font_size"));
+/// assert_eq!(changes.iter().collect::<Vec<_>>(), ["theme", "window.width"]);
+/// ```
+#[macro_export]
+macro_rules! assign_tracked {
+    (&mut $target:expr, {
+        $($entries:tt)+
+    }) => ({
+        let item = &mut $target;
+        let mut changes = $crate::Changes::new();
+        $crate::assign!(@entries (tracked (*item) changes) [] [()] $($entries)+);
+        changes
+    });
+    ($initial_value:expr, {
+        $($entries:tt)+
+    }) => ({
+        let mut item = $initial_value;
+        let mut changes = $crate::Changes::new();
+        $crate::assign!(@entries (tracked item changes) [] [()] $($entries)+);
+        (item, changes)
+    });
+}
+
+/// Build a [`Patch`] of a type instead of assigning to a value right away.
+///
+/// `patch!(T, { ... })` takes the same entries as [`assign!`] and returns the
+/// patch of `T` with the assigned fields set, leaving all others unset. The
+/// patch can be stored, combined with other patches using [`Patch::then`] and
+/// applied later with [`Patch::apply`].
+///
+/// Conditional, optional and shorthand entries behave as in [`assign!`], so an
+/// optional entry whose value is `None` leaves the field unset. Since the
+/// fields of a patch are only set on application, entries can only assign
+/// top-level fields.
+///
+#[cfg_attr(feature = "derive", doc = "```")]
+#[cfg_attr(not(feature = "derive"), doc = "```ignore")]
+/// use assign::{patch, Assign, Patch};
+///
+/// #[derive(Assign, Default)]
+/// #[assign(patch)]
+/// struct Config {
+///     host: String,
+///     port: u16,
+///     verbose: bool,
+/// }
+///
+/// let port = std::env::var("PORT").ok().and_then(|port| port.parse().ok());
+/// let overrides = patch!(Config, {
+///     host: "example.com".into(),
+///     port?: port,
+///     verbose if cfg!(debug_assertions): true,
+/// });
+///
+/// let mut config = Config::default();
+/// overrides.apply(&mut config);
+/// assert_eq!(config.host, "example.com");
+/// ```
+#[macro_export]
+macro_rules! patch {
+    ($target:ty, {
+        $($entries:tt)*
+    }) => ({
+        let mut patch = <<$target as $crate::Patchable>::Patch as ::core::default::Default>::default();
+        $crate::assign!(@entries (patch patch) [] [()] $($entries)*);
+        patch
+    });
+}
+
 #[cfg(test)]
 mod tests {
     #[derive(Debug, Default, PartialEq)]
@@ -188,6 +2044,767 @@ mod tests {
         );
     }
 
+    #[derive(Debug, Default, PartialEq)]
+    struct Outer {
+        x: u32,
+        inner: Inner,
+    }
+
+    #[derive(Debug, Default, PartialEq)]
+    struct Inner {
+        y: u32,
+        some: SomeStruct,
+    }
+
+    #[test]
+    fn nested_paths() {
+        let a = 3;
+        let res = assign!(Outer::default(), {
+            x: 1,
+            inner.y: 2,
+            inner.some.a,
+            inner.some.c: Some(4),
+        });
+
+        assert_eq!(
+            res,
+            Outer {
+                x: 1,
+                inner: Inner {
+                    y: 2,
+                    some: SomeStruct {
+                        a: 3,
+                        b: None,
+                        c: Some(4),
+                    },
+                },
+            }
+        );
+    }
+
+    #[test]
+    fn many_entries() {
+        // Assigns every column of every row, in a single block.
+        macro_rules! grid {
+            ([$($row:ident)*] [$($column:ident)*]) => {{
+                #[derive(Default)]
+                struct Row {
+                    $($column: u32,)*
+                }
+
+                #[derive(Default)]
+                struct Grid {
+                    $($row: Row,)*
+                }
+
+                grid!(@entries [$($row)*] [$($column)*] [])
+            }};
+            (@entries [$row:ident $($rows:ident)*] [$($column:ident)*] [$($entries:tt)*]) => {
+                grid!(@entries [$($rows)*] [$($column)*] [$($entries)* $($row.$column: 1,)*])
+            };
+            (@entries [] $columns:tt [$($entries:tt)*]) => {
+                assign!(Grid::default(), { $($entries)* r19.c15 += 1 })
+            };
+        }
+
+        // 320 entries
+        let grid = grid!(
+            [r0 r1 r2 r3 r4 r5 r6 r7 r8 r9 r10 r11 r12 r13 r14 r15 r16 r17 r18 r19]
+            [c0 c1 c2 c3 c4 c5 c6 c7 c8 c9 c10 c11 c12 c13 c14 c15]
+        );
+        assert_eq!(grid.r0.c0, 1);
+        assert_eq!(grid.r12.c7, 1);
+        assert_eq!(grid.r19.c15, 2);
+    }
+
+    #[test]
+    fn nested_blocks() {
+        let c = Some(6);
+        let res = assign!(Outer::default(), {
+            inner: {
+                y: 4,
+                some: {
+                    a: 5,
+                    c,
+                },
+            },
+            inner.some: { b: Some(1.0) },
+            x: 3,
+        });
+
+        assert_eq!(
+            res,
+            Outer {
+                x: 3,
+                inner: Inner {
+                    y: 4,
+                    some: SomeStruct {
+                        a: 5,
+                        b: Some(1.0),
+                        c: Some(6),
+                    },
+                },
+            }
+        );
+    }
+
+    #[derive(Debug, Default, PartialEq)]
+    struct Tuple(u32, Inner, (u8, u8));
+
+    #[test]
+    fn positional_fields() {
+        let y = 7;
+        let res = assign!(Tuple::default(), {
+            0: 1,
+            1.y,
+            1.some.a: 2,
+            2.1: 3,
+        });
+
+        assert_eq!(res.0, 1);
+        assert_eq!(res.1.y, 7);
+        assert_eq!(res.1.some.a, 2);
+        assert_eq!(res.2, (0, 3));
+    }
+
+    #[test]
+    fn compound_assignment() {
+        let res = assign!(Outer::default(), {
+            x: 10,
+            x -= 4,
+            x *= 3,
+            inner.y += 2,
+            inner: { y <<= 2, some.a ^= 1 },
+        });
+
+        assert_eq!(res.x, 18);
+        assert_eq!(res.inner.y, 8);
+        assert_eq!(res.inner.some.a, 1);
+    }
+
+    #[test]
+    fn method_calls() {
+        let res = assign!(Outer::default(), {
+            .inner.some.b.replace(1.0),
+            inner.some.c: Some(2),
+            .inner.some.c.take(),
+            inner: { .some.a.clone_from(&3) },
+        });
+
+        assert_eq!(res.inner.some.a, 3);
+        assert_eq!(res.inner.some.b, Some(1.0));
+        assert_eq!(res.inner.some.c, None);
+    }
+
+    #[test]
+    fn conditional_entries() {
+        let (yes, no) = (true, false);
+        let a = 9;
+        let res = assign!(Outer::default(), {
+            x if yes: 1,
+            inner.y if no: 2,
+            inner.some.a if yes,
+            inner if yes: { y: 3 },
+            if no {
+                x: 4,
+            } else if yes {
+                inner.some.b: Some(1.0),
+                if yes { inner.some.c: Some(5) }
+            } else {
+                x: 6,
+            },
+        });
+
+        assert_eq!(res.x, 1);
+        assert_eq!(res.inner.y, 3);
+        assert_eq!(res.inner.some.a, 9);
+        assert_eq!(res.inner.some.b, Some(1.0));
+        assert_eq!(res.inner.some.c, Some(5));
+    }
+
+    #[test]
+    fn optional_values() {
+        let a: Option<u32> = None;
+        let y = Some(2);
+        let res = assign!(Outer::default(), {
+            x?: Some(1),
+            x?: None,
+            inner.y?,
+            inner.some.a: 3,
+            inner.some.a?,
+            inner: { some.c?: Some(Some(4)) },
+        });
+
+        assert_eq!(res.x, 1);
+        assert_eq!(res.inner.y, 2);
+        assert_eq!(res.inner.some.a, 3);
+        assert_eq!(res.inner.some.c, Some(4));
+    }
+
+    #[derive(Debug, PartialEq)]
+    struct Invalid(&'static str);
+
+    impl From<()> for Invalid {
+        fn from(_: ()) -> Self {
+            Invalid("unit")
+        }
+    }
+
+    #[test]
+    fn try_assign_ok() {
+        let a: Result<u32, Invalid> = Ok(1);
+        let res: Result<Outer, crate::AssignErrors<Invalid>> = try_assign!(Outer::default(), {
+            inner.some.a,
+            inner: { y: Ok::<_, Invalid>(2) },
+            x: Ok::<_, Invalid>(3),
+            x += Ok::<_, Invalid>(1),
+            inner.some.c?: Ok::<_, Invalid>(None),
+        });
+
+        assert_eq!(
+            res.unwrap(),
+            Outer {
+                x: 4,
+                inner: Inner {
+                    y: 2,
+                    some: SomeStruct {
+                        a: 1,
+                        b: None,
+                        c: None,
+                    },
+                },
+            }
+        );
+    }
+
+    #[test]
+    fn try_assign_collects_errors() {
+        let res: Result<Outer, crate::AssignErrors<Invalid>> = try_assign!(Outer::default(), {
+            x: Err(Invalid("x")),
+            inner: {
+                y: Ok::<_, Invalid>(1),
+                some.a: Err(()),
+            },
+            inner.some.c?: Err(Invalid("c")),
+        });
+
+        let errors = res.unwrap_err();
+        assert_eq!(errors.len(), 3);
+        assert_eq!(errors.get("x"), Some(&Invalid("x")));
+        assert_eq!(errors.get("inner.some.a"), Some(&Invalid("unit")));
+        assert_eq!(errors.get("inner.some.c"), Some(&Invalid("c")));
+        assert_eq!(errors.get("inner.y"), None);
+    }
+
+    #[test]
+    fn assign_default() {
+        #[derive(Debug, Default, PartialEq)]
+        struct Wrapper<T> {
+            value: T,
+            count: usize,
+        }
+
+        impl<T> Wrapper<T> {
+            fn new(value: T) -> Self {
+                Self { value, count: 1 }
+            }
+        }
+
+        let res = assign_default!(SomeStruct, {
+            a: 5,
+            c: Some(1),
+        });
+        assert_eq!(
+            res,
+            SomeStruct {
+                a: 5,
+                b: None,
+                c: Some(1),
+            }
+        );
+
+        let res = assign_default!(Wrapper::<u8>, { value: 3 });
+        assert_eq!(res, Wrapper { value: 3, count: 0 });
+
+        let res = assign_default!(Wrapper<&str> => new("a"), { count += 1 });
+        assert_eq!(
+            res,
+            Wrapper {
+                value: "a",
+                count: 2
+            }
+        );
+
+        let res = assign_default!(Tuple => default, { 1.y: 2 });
+        assert_eq!(res.1.y, 2);
+    }
+
+    #[cfg(not(feature = "alloc"))]
+    #[test]
+    fn try_assign_truncates_errors() {
+        let res: Result<Tuple, crate::AssignErrors<()>> = try_assign!(Tuple::default(), {
+            0: Err(()), 1.some.a: Err(()), 1.some.b: Err(()), 1.some.c: Err(()), 2.0: Err(()),
+            2.1: Err(()), 1.y: Err(()), #[overwrite] 1.y: Err(()),
+            #[overwrite] 1.y: Err(()), #[overwrite] 1.y: Err(()),
+        });
+
+        let errors = res.unwrap_err();
+        assert_eq!(errors.len(), 8);
+        assert_eq!(errors.truncated(), 2);
+        assert_eq!(errors.iter().last().unwrap().field, "1.y");
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn transactions() {
+        let mut tuple = Tuple::default();
+        let y = Ok(3);
+        let res: Result<_, crate::AssignErrors<()>> = try_assign!(&mut tuple, transaction {
+            0: Ok(1),
+            1.y,
+            1.some.a?: Ok(Some(4)),
+            2.0 += Ok(5),
+            2.1: Err(()),
+            #[overwrite]
+            2.0: |value: &mut u8| value.checked_sub(1).map(|sub| *value = sub).ok_or(()),
+        });
+        let errors = res.unwrap_err();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors.get("2.1"), Some(&()));
+        assert_eq!(tuple, Tuple::default());
+
+        let res: Result<_, crate::AssignErrors<()>> = try_assign!(&mut tuple, transaction {
+            0: Ok(1),
+            1.y,
+        });
+        assert!(res.is_ok());
+        assert_eq!(tuple.0, 1);
+        assert_eq!(tuple.1.y, 3);
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn transaction_panics() {
+        extern crate std;
+
+        let mut tuple = Tuple::default();
+        let res = std::panic::catch_unwind(core::panic::AssertUnwindSafe(|| {
+            let _: Result<_, crate::AssignErrors<()>> = try_assign!(&mut tuple, transaction {
+                0: Ok(1),
+                2.1 += Ok(2),
+                1.y: if true { panic!("value failed") } else { Ok(2) },
+            });
+        }));
+        assert!(res.is_err());
+        assert_eq!(tuple, Tuple::default());
+
+        #[derive(Debug, Default, PartialEq)]
+        struct Log {
+            count: u8,
+            values: std::vec::Vec<u8>,
+        }
+
+        let mut log = Log::default();
+        let res = std::panic::catch_unwind(core::panic::AssertUnwindSafe(|| {
+            let _: Result<_, crate::AssignErrors<()>> = try_assign!(&mut log, transaction {
+                count: Ok(1),
+                .values.extend((0..5).inspect(|&value| assert!(value < 3, "value failed"))),
+            });
+        }));
+        assert!(res.is_err());
+        assert_eq!(log, Log::default());
+
+        let res = std::panic::catch_unwind(core::panic::AssertUnwindSafe(|| {
+            let _: Result<_, crate::AssignErrors<()>> = try_assign!(
+                &mut log,
+                transaction {
+                    count: Ok(1),
+                    values: |values: &mut std::vec::Vec<u8>| {
+                        values.push(7);
+                        if true {
+                            panic!("update failed")
+                        } else {
+                            Ok(())
+                        }
+                    },
+                }
+            );
+        }));
+        assert!(res.is_err());
+        assert_eq!(log, Log::default());
+    }
+
+    #[test]
+    fn in_place() {
+        let mut outer = Outer::default();
+        let y = 2;
+        let inner = assign!(&mut outer.inner, {
+            y,
+            some: { a: 1 },
+            .some.b.replace(3.0),
+        });
+        inner.some.c = Some(4);
+
+        let res: Result<_, crate::AssignErrors<Invalid>> = try_assign!(&mut outer, {
+            x: Ok::<_, Invalid>(5),
+            inner.y: Err(Invalid("y")),
+        });
+        assert!(res.is_err());
+
+        assert_eq!(
+            outer,
+            Outer {
+                x: 5,
+                inner: Inner {
+                    y: 2,
+                    some: SomeStruct {
+                        a: 1,
+                        b: Some(3.0),
+                        c: Some(4),
+                    },
+                },
+            }
+        );
+    }
+
+    #[derive(Debug, Default, PartialEq)]
+    struct Table {
+        cells: [u32; 4],
+        rows: [Inner; 2],
+    }
+
+    #[test]
+    fn index_entries() {
+        let res = assign!(Table::default(), {
+            cells[0]: 1,
+            cells[2..]: [3, 4],
+            cells[1] += 2,
+            rows[0].y: 5,
+            rows[1]: { y: 6, some.a: 7 },
+        });
+
+        assert_eq!(res.cells, [1, 2, 3, 4]);
+        assert_eq!(res.rows[0].y, 5);
+        assert_eq!(res.rows[1].y, 6);
+        assert_eq!(res.rows[1].some.a, 7);
+
+        let cells = assign!([0u8; 3], { [0]: 1, [1..]: &[2, 3][..] });
+        assert_eq!(cells, [1, 2, 3]);
+
+        let res: Result<_, crate::AssignErrors<Invalid>> = try_assign!(Table::default(), {
+            cells[0]: Ok::<_, Invalid>(1),
+            rows[1].y: Err(Invalid("y")),
+            cells[1..3]: Err::<[u32; 2], _>(Invalid("range")),
+        });
+        let errors = res.unwrap_err();
+        assert_eq!(errors.get("rows[1].y"), Some(&Invalid("y")));
+        assert_eq!(errors.get("cells[1..3]"), Some(&Invalid("range")));
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn map_entries() {
+        use alloc::collections::BTreeMap;
+
+        let mut map = assign!(BTreeMap::new(), { [1]: "a", [2]: "b" });
+        assign!(&mut map, { [2]: "c", [3]: "d" });
+
+        assert_eq!(
+            map.into_iter().collect::<alloc::vec::Vec<_>>(),
+            [(1, "a"), (2, "c"), (3, "d")]
+        );
+    }
+
+    impl SomeStruct {
+        fn with_a(self, a: u32) -> Self {
+            Self { a, ..self }
+        }
+
+        fn try_with_c(self, c: u64) -> Result<Self, Invalid> {
+            match c {
+                0 => Err(Invalid("c")),
+                c => Ok(Self { c: Some(c), ..self }),
+            }
+        }
+    }
+
+    #[test]
+    fn builder_methods() {
+        fn build(c: u64) -> Result<Outer, Invalid> {
+            Ok(assign!(Outer::default(), {
+                x: 1,
+                inner.some: {
+                    .with_a(2),
+                    b: Some(3.0),
+                    .try_with_c(c)?,
+                },
+            }))
+        }
+
+        let res = build(4).unwrap();
+        assert_eq!(res.x, 1);
+        assert_eq!(
+            res.inner.some,
+            SomeStruct {
+                a: 2,
+                b: Some(3.0),
+                c: Some(4),
+            }
+        );
+        assert_eq!(build(0), Err(Invalid("c")));
+
+        let res = assign!(SomeStruct::default(), { .with_a(5), b: None });
+        assert_eq!(res.a, 5);
+    }
+
+    #[cfg(feature = "derive")]
+    impl Inner {
+        fn set_y(&mut self, y: u32) {
+            self.y = y;
+        }
+
+        fn with_some(&mut self, some: SomeStruct) -> &mut Self {
+            self.some = some;
+            self
+        }
+    }
+
+    #[cfg(feature = "derive")]
+    #[test]
+    fn setter_methods() {
+        let some = SomeStruct {
+            a: 1,
+            b: None,
+            c: Some(2),
+        };
+        let res = assign!(Outer::default(), {
+            x: 3,
+            inner: {
+                set y: 4,
+                set(with_) some,
+            },
+        });
+        assert_eq!(res.x, 3);
+        assert_eq!(res.inner.y, 4);
+        assert_eq!(res.inner.some.c, Some(2));
+
+        let res = try_assign!(Outer::default(), {
+            inner: { set y: Err::<u32, _>(()) },
+        });
+        assert_eq!(res.unwrap_err().get("inner.y"), Some(&()));
+    }
+
+    #[test]
+    fn overwrite_entries() {
+        let res = assign!(Outer::default(), {
+            x: 1,
+            inner.y: 2,
+            inner.some: { a: 3 },
+            #[overwrite]
+            x: 4,
+            #[overwrite]
+            inner.y += 5,
+            inner.some.a: 6,
+            inner: { y: 8 },
+        });
+
+        assert_eq!(res.x, 4);
+        assert_eq!(res.inner.y, 8);
+        assert_eq!(res.inner.some.a, 6);
+    }
+
+    #[test]
+    fn update_entries() {
+        let res = assign!(Tuple::default(), {
+            0: 5,
+            0: |old| old.min(3),
+            1.y: |y| y + 1,
+            1.some: {
+                b: |_| Some(1.0),
+                c if true: |c| c.or(Some(4)),
+                a if false: |_| 7,
+            },
+            2: (1, 2),
+            2: |pair: &mut _| core::mem::swap(&mut pair.0, &mut pair.1),
+        });
+        assert_eq!(res.0, 3);
+        assert_eq!(res.1.y, 1);
+        assert_eq!(res.1.some.a, 0);
+        assert_eq!(res.1.some.b, Some(1.0));
+        assert_eq!(res.1.some.c, Some(4));
+        assert_eq!(res.2, (2, 1));
+
+        let res = assign!(
+            Shape::Rect(2, 3),
+            Shape::Rect {
+                1: |height| height * 2
+            }
+        );
+        assert_eq!(res, Shape::Rect(2, 6));
+
+        let res: Result<Outer, crate::AssignErrors<()>> = try_assign!(Outer::default(), {
+            x: |x: &mut u32| x.checked_sub(1).map(|value| *x = value).ok_or(()),
+            inner.y: |y: &mut u32| y.checked_add(1).map(|value| *y = value).ok_or(()),
+        });
+        let errors = res.unwrap_err();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors.get("x"), Some(&()));
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn replaced_values() {
+        let y = 4;
+        let (mut res, replaced) = assign_replace!(Tuple::default(), {
+            0: 1,
+            1.y,
+            1.some.c: Some(2),
+            #[overwrite]
+            0 += 2,
+            2.1: 5,
+        });
+        assert!(replaced.fields().eq(["0", "1.y", "1.some.c", "0", "2.1"]));
+        assert_eq!(res.0, 3);
+        assert_eq!(res.1.y, 4);
+        assert_eq!(res.2, (0, 5));
+
+        replaced.restore(&mut res);
+        assert_eq!(res, Tuple::default());
+
+        let mut outer = Outer::default();
+        let replaced = assign_replace!(&mut outer, {
+            x: 2,
+            inner.y: |y| y + 1,
+            inner.some.c?: None,
+        });
+        assert_eq!(replaced.len(), 2);
+        assert_eq!(outer.x, 2);
+        assert_eq!(outer.inner.y, 1);
+
+        replaced.restore(&mut outer);
+        assert_eq!(outer, Outer::default());
+    }
+
+    #[test]
+    fn tracked_changes() {
+        struct Opaque(u8);
+
+        struct Tracked {
+            inner: Inner,
+            opaque: Opaque,
+        }
+
+        let y = 0;
+        let (res, changes) = assign_tracked!(Tracked { inner: Inner::default(), opaque: Opaque(0) }, {
+            inner.y,
+            inner.some.a: 2,
+            inner.some.c: None,
+            opaque: Opaque(0),
+        });
+        assert_eq!(res.inner.some.a, 2);
+        assert_eq!(res.opaque.0, 0);
+        assert!(!changes.is_empty());
+        assert!(!changes.contains("inner.y"));
+        assert!(changes.contains("inner.some.a"));
+        assert!(changes.contains("opaque"));
+
+        let mut outer = Outer::default();
+        let changes = assign_tracked!(&mut outer, { x: 0 });
+        assert!(changes.is_empty());
+        let changes = assign_tracked!(&mut outer, {
+            x: 1,
+            #[overwrite]
+            x: 1,
+            inner.y += 0,
+            .inner.some.b.replace(0.0),
+        });
+        assert_eq!(changes.len(), 2);
+        assert!(changes.iter().eq(["x", "inner.some.b"]));
+
+        struct Bytes {
+            data: &'static [u8],
+        }
+
+        let (res, changes) = assign_tracked!(Bytes { data: b"abc" }, { data: b"abd" });
+        assert_eq!(res.data, b"abd");
+        assert!(changes.contains("data"));
+
+        let mut table = Table::default();
+        let changes = assign_tracked!(&mut table, {
+            cells[0]: 0,
+            cells[1]: 1,
+            rows[0].y: |y| y + 1,
+        });
+        assert!(changes.iter().eq(["cells[1]", "rows[0].y"]));
+    }
+
+    #[test]
+    fn named_item() {
+        let res = assign!(Outer::default(), |outer| {
+            x: 2,
+            inner.y: outer.x * 3,
+            inner.some: { a: outer.inner.y + 1 },
+        });
+        assert_eq!(res.x, 2);
+        assert_eq!(res.inner.y, 6);
+        assert_eq!(res.inner.some.a, 7);
+
+        let mut outer = Outer::default();
+        let res = assign!(&mut outer, |item| {
+            x: 4,
+            inner.y: item.x + 1,
+        });
+        res.x += 1;
+        assert_eq!(outer.x, 5);
+        assert_eq!(outer.inner.y, 5);
+    }
+
+    #[derive(Debug, PartialEq)]
+    enum Shape {
+        Circle { radius: u32, center: (i32, i32) },
+        Rect(u32, u32),
+        Empty,
+    }
+
+    #[test]
+    fn enum_variants() {
+        let circle = Shape::Circle {
+            radius: 1,
+            center: (0, 0),
+        };
+        let res = assign!(circle, Shape::Circle {
+            radius: 2,
+            center.1: 3,
+        });
+        assert_eq!(
+            res,
+            Shape::Circle {
+                radius: 2,
+                center: (0, 3),
+            }
+        );
+
+        let mut rect = Shape::Rect(1, 2);
+        assign!(&mut rect, Shape::Rect { 1 *= 2 });
+        assert_eq!(rect, Shape::Rect(1, 4));
+
+        let res = assign!(Shape::Empty, Shape::Rect { 0: 5 } else ignore);
+        assert_eq!(res, Shape::Empty);
+
+        let res = assign!(&mut rect, Shape::Rect { 0: 5 } else error);
+        assert_eq!(res, Ok(&mut Shape::Rect(5, 4)));
+
+        let err = assign!(Shape::Empty, Shape::Circle { radius: 5 } else error).unwrap_err();
+        assert_eq!(err.expected(), "Shape::Circle");
+        assert_eq!(err.into_inner(), Shape::Empty);
+    }
+
+    #[test]
+    #[should_panic(expected = "expected variant `Shape::Circle`")]
+    fn enum_variant_mismatch() {
+        assign!(Shape::Empty, Shape::Circle { radius: 5 });
+    }
+
     #[test]
     fn all_fields() {
         let a = 1;
@@ -0,0 +1,67 @@
+/// Information about the fields of a struct.
+///
+/// This is usually implemented with `#[derive(Assign)]`, which requires the
+/// `derive` feature.
+///
+#[cfg_attr(feature = "derive", doc = "```")]
+#[cfg_attr(not(feature = "derive"), doc = "```ignore")]
+/// use assign::Assign;
+///
+/// #[derive(Assign)]
+/// struct Point(i32, i32);
+///
+/// assert_eq!(Point::FIELDS.len(), 2);
+/// assert_eq!(Point::FIELDS[1].name, "1");
+/// assert_eq!(Point::FIELDS[1].ty, "i32");
+/// ```
+pub trait Assign {
+    /// The fields of the struct, in declaration order.
+    const FIELDS: &'static [Field];
+
+    /// Look up a field by its name.
+    fn field(name: &str) -> Option<&'static Field> {
+        Self::FIELDS.iter().find(|field| field.name == name)
+    }
+}
+
+/// A field of a struct implementing [`Assign`].
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Field {
+    /// The name of the field, or its position for tuple structs.
+    pub name: &'static str,
+    /// The type of the field, as written in the struct definition.
+    pub ty: &'static str,
+    /// The visibility of the field.
+    pub visibility: Visibility,
+}
+
+impl Field {
+    #[doc(hidden)]
+    pub const fn new(name: &'static str, ty: &'static str, visibility: Visibility) -> Self {
+        Self {
+            name,
+            ty,
+            visibility,
+        }
+    }
+}
+
+/// The visibility of a [`Field`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Visibility {
+    /// `pub`
+    Public,
+    /// `pub(crate)`, `pub(super)` or `pub(in path)`, holding what is written
+    /// between the parentheses.
+    Restricted(&'static str),
+    /// No visibility modifier, i.e. private to the defining module.
+    Inherited,
+}
+
+impl Visibility {
+    /// Whether the field is visible outside of the crate defining it.
+    pub fn is_public(self) -> bool {
+        self == Visibility::Public
+    }
+}
@@ -0,0 +1,143 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+
+use serde::de::{DeserializeSeed, Deserializer, Error as _, MapAccess, Visitor};
+
+/// Deserialization of a partial document onto an existing value.
+///
+/// This is usually implemented with `#[derive(ApplyPartial)]`, which requires
+/// the `derive` feature. Only the fields present in the document are assigned,
+/// and unknown fields are rejected. Fields marked with `#[assign(nested)]` are
+/// updated with their own `ApplyPartial` implementation instead of being
+/// replaced as a whole.
+///
+#[cfg_attr(feature = "derive", doc = "```")]
+#[cfg_attr(not(feature = "derive"), doc = "```ignore")]
+/// use assign::ApplyPartial;
+///
+/// #[derive(ApplyPartial)]
+/// struct Config {
+///     name: String,
+///     #[assign(nested)]
+///     server: Server,
+/// }
+///
+/// #[derive(ApplyPartial)]
+/// struct Server {
+///     host: String,
+///     port: u16,
+/// }
+///
+/// let mut config = Config {
+///     name: "app".into(),
+///     server: Server { host: "localhost".into(), port: 80 },
+/// };
+///
+/// let mut document = serde_json::Deserializer::from_str(r#"{ "server": { "port": 8080 } }"#);
+/// config.apply_partial(&mut document).unwrap();
+/// assert_eq!(config.name, "app");
+/// assert_eq!(config.server.host, "localhost");
+/// assert_eq!(config.server.port, 8080);
+///
+/// let mut document = serde_json::Deserializer::from_str(r#"{ "server": { "port": "http" } }"#);
+/// let error = config.apply_partial(&mut document).unwrap_err();
+/// assert_eq!(error.field, "server.port");
+/// ```
+pub trait ApplyPartial {
+    #[doc(hidden)]
+    const PARTIAL_FIELDS: &'static [&'static str];
+
+    #[doc(hidden)]
+    fn apply_partial_field<'de, A: MapAccess<'de>>(
+        &mut self,
+        field: &'static str,
+        map: &mut A,
+        path: &mut Vec<&'static str>,
+    ) -> Result<(), A::Error>;
+
+    /// Assign the fields present in the document read by `deserializer`.
+    ///
+    /// Fields read before an error occurs stay assigned.
+    fn apply_partial<'de, D: Deserializer<'de>>(
+        &mut self,
+        deserializer: D,
+    ) -> Result<(), PartialError<D::Error>>
+    where
+        Self: Sized,
+    {
+        let mut path = Vec::new();
+        PartialSeed::new(self, &mut path)
+            .deserialize(deserializer)
+            .map_err(|error| {
+                path.reverse();
+                PartialError {
+                    field: path.join("."),
+                    error,
+                }
+            })
+    }
+}
+
+/// An error produced by [`ApplyPartial::apply_partial`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PartialError<E> {
+    /// The path of the field that failed, e.g. `"server.port"`, or an empty
+    /// string if the document itself is malformed.
+    pub field: String,
+    /// The error of the deserializer.
+    pub error: E,
+}
+
+impl<E: fmt::Display> fmt::Display for PartialError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.field.is_empty() {
+            write!(f, "{}", self.error)
+        } else {
+            write!(f, "{}: {}", self.field, self.error)
+        }
+    }
+}
+
+/// Applies a partial document to the value it borrows, recording the path of
+/// the failing field in reverse.
+pub struct PartialSeed<'a, T> {
+    target: &'a mut T,
+    path: &'a mut Vec<&'static str>,
+}
+
+impl<'a, T> PartialSeed<'a, T> {
+    pub fn new(target: &'a mut T, path: &'a mut Vec<&'static str>) -> Self {
+        Self { target, path }
+    }
+}
+
+impl<'de, T: ApplyPartial> DeserializeSeed<'de> for PartialSeed<'_, T> {
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        deserializer.deserialize_map(self)
+    }
+}
+
+impl<'de, T: ApplyPartial> Visitor<'de> for PartialSeed<'_, T> {
+    type Value = ();
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a map of fields")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<(), A::Error> {
+        while let Some(key) = map.next_key::<String>()? {
+            let field = match T::PARTIAL_FIELDS.iter().find(|field| **field == key) {
+                Some(field) => *field,
+                None => return Err(A::Error::unknown_field(&key, T::PARTIAL_FIELDS)),
+            };
+            if let Err(error) = self.target.apply_partial_field(field, &mut map, self.path) {
+                self.path.push(field);
+                return Err(error);
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,46 @@
+/// A set of field assignments that can be stored and applied later.
+///
+/// Patches are usually generated by `#[derive(Assign)]` with
+/// `#[assign(patch)]`, which requires the `derive` feature, and built with
+/// [`patch!`].
+///
+#[cfg_attr(feature = "derive", doc = "```")]
+#[cfg_attr(not(feature = "derive"), doc = "```ignore")]
+/// use assign::{patch, Assign, Patch};
+///
+/// #[derive(Assign, Debug, PartialEq)]
+/// #[assign(patch)]
+/// struct Window {
+///     width: u32,
+///     height: u32,
+///     title: &'static str,
+/// }
+///
+/// let defaults = patch!(Window, { width: 640, height: 480 });
+/// let user = patch!(Window, { height: 600, title: "editor" });
+///
+/// let mut window = Window { width: 0, height: 0, title: "" };
+/// defaults.then(user).apply(&mut window);
+/// assert_eq!(window, Window { width: 640, height: 600, title: "editor" });
+/// ```
+///
+/// [`patch!`]: crate::patch
+pub trait Patch: Default {
+    /// The type the patch applies to.
+    type Target;
+
+    /// Assign every field set in the patch to `target`.
+    fn apply(self, target: &mut Self::Target);
+
+    /// Combine two patches into one applying `self` and then `next`, so
+    /// fields set in both take their value from `next`.
+    fn then(self, next: Self) -> Self;
+}
+
+/// A type with a [`Patch`] type, which [`patch!`] builds for it.
+///
+/// [`patch!`]: crate::patch
+pub trait Patchable {
+    /// The patch of this type.
+    type Patch: Patch<Target = Self>;
+}
@@ -0,0 +1,98 @@
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::{fmt, mem};
+
+type Restore<'a, T> = Box<dyn FnOnce(&mut T) + 'a>;
+
+/// The values replaced by an [`assign_replace!`] invocation.
+///
+/// The replaced values are kept in the order of the entries and can be
+/// written back with [`restore`](Self::restore).
+///
+/// [`assign_replace!`]: crate::assign_replace
+pub struct Replaced<'a, T: ?Sized> {
+    entries: Vec<(&'static str, Restore<'a, T>)>,
+}
+
+impl<'a, T: ?Sized> Replaced<'a, T> {
+    #[doc(hidden)]
+    pub fn new(_target: &T) -> Self {
+        Self {
+            entries: Vec::new(),
+        }
+    }
+
+    #[doc(hidden)]
+    pub fn push(&mut self, field: &'static str, restore: impl FnOnce(&mut T) + 'a) {
+        self.entries.push((field, Box::new(restore)));
+    }
+
+    /// The number of entries that replaced a value.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether no value was replaced.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Iterate over the paths of the replaced fields, in the order of the
+    /// entries.
+    pub fn fields(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.entries.iter().map(|(field, _)| *field)
+    }
+
+    /// Write the replaced values back to their fields of `target`.
+    ///
+    /// Fields are restored in the reverse order of the entries, so a field
+    /// replaced more than once gets its original value back.
+    pub fn restore(self, target: &mut T) {
+        for (_, restore) in self.entries.into_iter().rev() {
+            restore(target);
+        }
+    }
+}
+
+impl<T: ?Sized> fmt::Debug for Replaced<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.fields()).finish()
+    }
+}
+
+/// The state of a transactional [`try_assign!`], which restores the replaced
+/// values when dropped unless it was committed.
+///
+/// [`try_assign!`]: crate::try_assign
+#[doc(hidden)]
+pub struct Transaction<'a, T: ?Sized> {
+    pub target: &'a mut T,
+    pub replaced: Replaced<'a, T>,
+}
+
+impl<'a, T: ?Sized> Transaction<'a, T> {
+    pub fn new(target: &'a mut T) -> Self {
+        Self {
+            target,
+            replaced: Replaced {
+                entries: Vec::new(),
+            },
+        }
+    }
+
+    pub fn commit(mut self) {
+        self.replaced.entries.clear();
+    }
+}
+
+impl<T: ?Sized> Drop for Transaction<'_, T> {
+    fn drop(&mut self) {
+        let replaced = mem::replace(
+            &mut self.replaced,
+            Replaced {
+                entries: Vec::new(),
+            },
+        );
+        replaced.restore(self.target);
+    }
+}
@@ -0,0 +1,185 @@
+use core::fmt;
+
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+/// Number of changed fields kept by [`Changes`] when the `alloc` feature is
+/// disabled.
+#[cfg(not(feature = "alloc"))]
+const CAPACITY: usize = 16;
+
+/// The fields changed by an [`assign_tracked!`] invocation, in the order of
+/// the entries that first changed them.
+///
+/// Fields are named by their path as written in the entries, e.g.
+/// `"server.port"`, and are kept once even if several entries changed them.
+/// With the `alloc` feature, every changed field is kept. Without it, only the
+/// first sixteen are kept and the number of further changes is available from
+/// [`truncated`](Self::truncated).
+///
+/// [`assign_tracked!`]: crate::assign_tracked
+#[derive(Clone, PartialEq, Eq)]
+pub struct Changes {
+    #[cfg(feature = "alloc")]
+    fields: Vec<&'static str>,
+    #[cfg(not(feature = "alloc"))]
+    fields: [Option<&'static str>; CAPACITY],
+    #[cfg(not(feature = "alloc"))]
+    len: usize,
+    #[cfg(not(feature = "alloc"))]
+    truncated: usize,
+}
+
+impl Changes {
+    #[doc(hidden)]
+    pub fn new() -> Self {
+        Self {
+            #[cfg(feature = "alloc")]
+            fields: Vec::new(),
+            #[cfg(not(feature = "alloc"))]
+            fields: [None; CAPACITY],
+            #[cfg(not(feature = "alloc"))]
+            len: 0,
+            #[cfg(not(feature = "alloc"))]
+            truncated: 0,
+        }
+    }
+
+    #[doc(hidden)]
+    pub fn mark(&mut self, field: &'static str) {
+        if self.contains(field) {
+            return;
+        }
+
+        #[cfg(feature = "alloc")]
+        self.fields.push(field);
+
+        #[cfg(not(feature = "alloc"))]
+        match self.fields.get_mut(self.len) {
+            Some(slot) => {
+                *slot = Some(field);
+                self.len += 1;
+            }
+            None => self.truncated += 1,
+        }
+    }
+
+    /// The number of changed fields kept.
+    pub fn len(&self) -> usize {
+        self.iter().count()
+    }
+
+    /// Whether no field changed.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0 && self.truncated() == 0
+    }
+
+    /// The number of changes that were not kept because the set was full.
+    ///
+    /// A field changed by several entries may be counted more than once. This
+    /// is always zero with the `alloc` feature.
+    pub fn truncated(&self) -> usize {
+        #[cfg(feature = "alloc")]
+        return 0;
+
+        #[cfg(not(feature = "alloc"))]
+        return self.truncated;
+    }
+
+    /// Whether the field at `field` changed.
+    pub fn contains(&self, field: &str) -> bool {
+        self.iter().any(|changed| changed == field)
+    }
+
+    /// Iterate over the paths of the changed fields kept.
+    pub fn iter(&self) -> impl Iterator<Item = &'static str> + '_ {
+        #[cfg(feature = "alloc")]
+        return self.fields.iter().copied();
+
+        #[cfg(not(feature = "alloc"))]
+        return self.fields.iter().flatten().copied();
+    }
+}
+
+impl Default for Changes {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for Changes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+/// Return `value`, typed as the field it is compared with and assigned to.
+///
+/// Taking the value as an argument of the field type applies the coercions a
+/// plain assignment would, e.g. from `&[u8; 3]` to `&[u8]`.
+pub fn same_type<T>(_field: &T, value: T) -> T {
+    value
+}
+
+/// The value an [`assign_tracked!`] entry found in its field and the value
+/// it leaves there.
+///
+/// The field is reported as changed if `changed()` returns true. Values of
+/// types implementing `PartialEq` are compared with `!=`, so assigning an
+/// equal value is not a change. Other types can't be compared, and every
+/// assignment to them counts as a change. The `CompareAny` fallback is only
+/// picked when `CompareEq` doesn't apply, as its `&self` receiver needs an
+/// extra autoref.
+///
+/// [`assign_tracked!`]: crate::assign_tracked
+pub struct Compare<'a, T>(pub &'a T, pub &'a T);
+
+pub trait CompareEq {
+    fn changed(self) -> bool;
+}
+
+pub trait CompareAny {
+    fn changed(&self) -> bool;
+}
+
+impl<T: PartialEq> CompareEq for Compare<'_, T> {
+    fn changed(self) -> bool {
+        self.0 != self.1
+    }
+}
+
+impl<T> CompareAny for Compare<'_, T> {
+    fn changed(&self) -> bool {
+        true
+    }
+}
+
+/// The value of a field an [`assign_tracked!`] entry is about to change in
+/// place, e.g. with a compound assignment or a method call.
+///
+/// A copy is only kept if the field type is `Clone` and `PartialEq`, as
+/// nothing else could be compared afterwards. Without a copy, the entry
+/// counts as a change.
+///
+/// [`assign_tracked!`]: crate::assign_tracked
+pub struct Snapshot<'a, T>(pub &'a T);
+
+pub trait SnapshotEq<T> {
+    fn snapshot(self) -> Option<T>;
+}
+
+pub trait SnapshotAny<T> {
+    fn snapshot(&self) -> Option<T>;
+}
+
+impl<T: Clone + PartialEq> SnapshotEq<T> for Snapshot<'_, T> {
+    fn snapshot(self) -> Option<T> {
+        Some(self.0.clone())
+    }
+}
+
+impl<T> SnapshotAny<T> for Snapshot<'_, T> {
+    fn snapshot(&self) -> Option<T> {
+        None
+    }
+}
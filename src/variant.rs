@@ -0,0 +1,36 @@
+use core::fmt;
+
+/// The error of an [`assign!`] invocation with `else error` whose value is not
+/// of the expected enum variant.
+///
+/// It holds the value the entries were meant for, which was left untouched.
+///
+/// [`assign!`]: crate::assign
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VariantMismatch<T> {
+    value: T,
+    expected: &'static str,
+}
+
+impl<T> VariantMismatch<T> {
+    #[doc(hidden)]
+    pub fn new(value: T, expected: &'static str) -> Self {
+        Self { value, expected }
+    }
+
+    /// The path of the expected variant, as written in the invocation.
+    pub fn expected(&self) -> &'static str {
+        self.expected
+    }
+
+    /// The value the entries were meant for.
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+}
+
+impl<T> fmt::Display for VariantMismatch<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "expected variant `{}`", self.expected)
+    }
+}
@@ -0,0 +1,12 @@
+// Diagnostics are spanned at the offending entry only with the `derive`
+// feature, so the expected output is pinned for that configuration. It also
+// depends on the compiler version, so the test is ignored by default and CI
+// runs it with `--ignored` on the toolchain pinned for it.
+#![cfg(feature = "derive")]
+
+#[test]
+#[ignore = "the expected diagnostics depend on the compiler version"]
+fn ui() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/*.rs");
+}
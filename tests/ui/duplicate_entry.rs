@@ -0,0 +1,42 @@
+use assign::assign;
+
+#[derive(Default)]
+struct Server {
+    host: &'static str,
+    port: u16,
+    limits: Limits,
+}
+
+#[derive(Default)]
+struct Limits {
+    connections: u32,
+}
+
+#[derive(Default)]
+struct Pair(u8, u8);
+
+fn main() {
+    let _ = assign!(Server::default(), {
+        host: "localhost",
+        port: 80,
+        limits.connections: 10,
+        port: 8080,
+        limits.connections: 20,
+    });
+
+    let _ = assign!(Pair::default(), {
+        0: 1,
+        1: 2,
+        0: 3,
+    });
+
+    let _ = assign!(Server::default(), {
+        limits.connections: 10,
+        limits: Limits::default(),
+    });
+
+    let _ = assign!(Server::default(), {
+        limits: Limits::default(),
+        limits.connections: 10,
+    });
+}
@@ -0,0 +1,47 @@
+use assign::{assign, patch, try_assign, Assign, AssignErrors};
+
+#[derive(Default)]
+struct Pair(u32, u32);
+
+#[derive(Assign, Default)]
+#[assign(patch)]
+struct Point {
+    x: i32,
+    y: i32,
+}
+
+impl Point {
+    fn set_x(&mut self, x: i32) {
+        self.x = x;
+    }
+
+    fn try_with_y(self, y: i32) -> Result<Self, ()> {
+        Ok(Point { y, ..self })
+    }
+}
+
+fn parse() -> Result<Point, AssignErrors<()>> {
+    try_assign!(Point::default(), {
+        x: Ok(1),
+        .try_with_y(2)?,
+    })
+}
+
+fn main() {
+    let _ = parse();
+
+    let _ = assign!(Pair::default(), {
+        0: 1,
+        1,
+    });
+
+    let _ = assign!(Point::default(), {
+        x = 1,
+        set z: 2,
+    });
+
+    let _ = patch!(Point, {
+        x += 1,
+        set x: 2,
+    });
+}
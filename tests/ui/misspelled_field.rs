@@ -0,0 +1,27 @@
+use assign::assign;
+
+#[derive(Default)]
+struct Style {
+    color: u32,
+    width: u32,
+    border: Border,
+}
+
+#[derive(Default)]
+struct Border {
+    radius: u32,
+}
+
+fn main() {
+    let widht = 2;
+    let mut style = assign!(Style::default(), {
+        colr: 1,
+        widht,
+        border.raduis: 3,
+        border: { radis: 4 },
+    });
+
+    assign!(&mut style, {
+        colour += 1,
+    });
+}
@@ -0,0 +1,18 @@
+use assign::assign;
+
+mod theme {
+    #[derive(Default)]
+    pub struct Theme {
+        pub background: u32,
+        pub foreground: u32,
+        pub(crate) accent: u32,
+        palette: u32,
+    }
+}
+
+fn main() {
+    let _ = assign!(theme::Theme::default(), {
+        size: 12,
+        palette: 1,
+    });
+}